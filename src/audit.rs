@@ -0,0 +1,282 @@
+use crate::colors::Colorize;
+use crate::http_check::{redact_url, with_prefix};
+use crate::https::HttpReply;
+use crate::parser::Parser;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 180 days, the minimum for HSTS preload lists and common scanners.
+const HSTS_MIN_AGE: u64 = 15_552_000;
+const CERT_WARN_DAYS: i64 = 14;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Grade {
+    Pass,
+    Warn,
+    Fail,
+}
+
+struct Finding {
+    grade: Grade,
+    item: &'static str,
+    detail: String,
+}
+
+fn finding(grade: Grade, item: &'static str, detail: impl Into<String>) -> Finding {
+    Finding {
+        grade,
+        item,
+        detail: detail.into(),
+    }
+}
+
+fn directive<'a>(value: &'a str, name: &str) -> Option<&'a str> {
+    value.split(';').find_map(|part| {
+        let part = part.trim();
+        let (key, rest) = part.split_once(['=', ' ']).unwrap_or((part, ""));
+        key.eq_ignore_ascii_case(name).then(|| rest.trim())
+    })
+}
+
+fn check_hsts(reply: &HttpReply, https: bool) -> Finding {
+    const ITEM: &str = "Strict-Transport-Security";
+    if !https {
+        return finding(Grade::Fail, ITEM, "cannot be set over plain HTTP");
+    }
+    let Some(value) = reply.header(ITEM) else {
+        return finding(Grade::Fail, ITEM, "missing");
+    };
+    match directive(value, "max-age").and_then(|age| age.trim_matches('"').parse::<u64>().ok()) {
+        Some(age) if age >= HSTS_MIN_AGE => finding(Grade::Pass, ITEM, value),
+        Some(age) => finding(
+            Grade::Warn,
+            ITEM,
+            format!("max-age={age} is shorter than 180 days"),
+        ),
+        None => finding(Grade::Fail, ITEM, format!("invalid max-age in '{value}'")),
+    }
+}
+
+fn check_csp(reply: &HttpReply) -> Finding {
+    const ITEM: &str = "Content-Security-Policy";
+    let Some(value) = reply.header(ITEM) else {
+        return if reply
+            .header("Content-Security-Policy-Report-Only")
+            .is_some()
+        {
+            finding(Grade::Warn, ITEM, "report-only, not enforced")
+        } else {
+            finding(Grade::Fail, ITEM, "missing")
+        };
+    };
+    let lower = value.to_ascii_lowercase();
+    let mut weak = Vec::new();
+    for keyword in ["'unsafe-inline'", "'unsafe-eval'"] {
+        if lower.contains(keyword) {
+            weak.push(keyword.to_string());
+        }
+    }
+    let wildcard = ["default-src", "script-src"].iter().any(|name| {
+        directive(&lower, name).is_some_and(|sources| sources.split_whitespace().any(|s| s == "*"))
+    });
+    if wildcard {
+        weak.push("wildcard script source".to_string());
+    }
+    if weak.is_empty() {
+        finding(Grade::Pass, ITEM, "present")
+    } else {
+        finding(Grade::Warn, ITEM, format!("allows {}", weak.join(", ")))
+    }
+}
+
+fn check_content_type_options(reply: &HttpReply) -> Finding {
+    const ITEM: &str = "X-Content-Type-Options";
+    match reply.header(ITEM) {
+        Some(value) if value.trim().eq_ignore_ascii_case("nosniff") => {
+            finding(Grade::Pass, ITEM, "nosniff")
+        }
+        Some(value) => finding(Grade::Fail, ITEM, format!("'{value}' is not nosniff")),
+        None => finding(Grade::Fail, ITEM, "missing"),
+    }
+}
+
+fn check_frame_options(reply: &HttpReply) -> Finding {
+    const ITEM: &str = "X-Frame-Options";
+    let frame_ancestors = reply
+        .header("Content-Security-Policy")
+        .is_some_and(|csp| directive(csp, "frame-ancestors").is_some());
+    match reply.header(ITEM).map(str::trim) {
+        Some(value)
+            if value.eq_ignore_ascii_case("DENY") || value.eq_ignore_ascii_case("SAMEORIGIN") =>
+        {
+            finding(Grade::Pass, ITEM, value.to_ascii_uppercase())
+        }
+        _ if frame_ancestors => finding(Grade::Pass, ITEM, "CSP frame-ancestors"),
+        Some(value) => finding(Grade::Warn, ITEM, format!("unsupported value '{value}'")),
+        None => finding(Grade::Fail, ITEM, "missing"),
+    }
+}
+
+fn check_referrer_policy(reply: &HttpReply) -> Finding {
+    const ITEM: &str = "Referrer-Policy";
+    let Some(value) = reply.header(ITEM) else {
+        return finding(Grade::Warn, ITEM, "missing (browser default applies)");
+    };
+    // With a comma-separated list the last policy wins, as in browsers.
+    let policy = value
+        .split(',')
+        .map(|p| p.trim().to_ascii_lowercase())
+        .next_back()
+        .unwrap_or_default();
+    match policy.as_str() {
+        "no-referrer" | "same-origin" | "strict-origin" | "strict-origin-when-cross-origin" => {
+            finding(Grade::Pass, ITEM, policy)
+        }
+        "unsafe-url" | "no-referrer-when-downgrade" | "origin" | "origin-when-cross-origin" => {
+            finding(
+                Grade::Warn,
+                ITEM,
+                format!("{policy} leaks URLs to other sites"),
+            )
+        }
+        _ => finding(Grade::Warn, ITEM, format!("unknown policy '{value}'")),
+    }
+}
+
+fn check_cookies(reply: &HttpReply, https: bool) -> Option<Finding> {
+    const ITEM: &str = "Cookie flags";
+    let cookies: Vec<&str> = reply
+        .headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("Set-Cookie"))
+        .map(|(_, value)| value.as_str())
+        .collect();
+    if cookies.is_empty() {
+        return None;
+    }
+    let mut grade = Grade::Pass;
+    let mut problems = Vec::new();
+    for cookie in &cookies {
+        let name = cookie.split(['=', ';']).next().unwrap_or_default().trim();
+        let has = |flag: &str| directive(cookie, flag).is_some();
+        let mut missing = Vec::new();
+        if https && !has("Secure") {
+            missing.push("Secure");
+            grade = Grade::Fail;
+        }
+        if !has("HttpOnly") {
+            missing.push("HttpOnly");
+        }
+        if !has("SameSite") {
+            missing.push("SameSite");
+        }
+        if !missing.is_empty() {
+            if grade == Grade::Pass {
+                grade = Grade::Warn;
+            }
+            problems.push(format!("{name} lacks {}", missing.join("/")));
+        }
+    }
+    Some(if problems.is_empty() {
+        finding(grade, ITEM, format!("{} cookie(s) OK", cookies.len()))
+    } else {
+        finding(grade, ITEM, problems.join(", "))
+    })
+}
+
+fn check_https_redirect(first_redirect: Option<&str>) -> Finding {
+    const ITEM: &str = "HTTPS redirect";
+    match first_redirect {
+        Some(location) if location.to_ascii_lowercase().starts_with("https://") => {
+            finding(Grade::Pass, ITEM, format!("redirects to {location}"))
+        }
+        Some(location) => finding(
+            Grade::Fail,
+            ITEM,
+            format!("redirects to {location}, not to HTTPS"),
+        ),
+        None => finding(Grade::Fail, ITEM, "plain HTTP is served without a redirect"),
+    }
+}
+
+fn check_certificate(reply: &HttpReply) -> Option<Finding> {
+    const ITEM: &str = "Certificate expiry";
+    let not_after = reply.peer?.not_after?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX));
+    let days = (not_after - now).div_euclid(86_400);
+    Some(match days {
+        ..0 => finding(Grade::Fail, ITEM, "expired"),
+        0..CERT_WARN_DAYS => finding(Grade::Warn, ITEM, format!("expires in {days} day(s)")),
+        _ => finding(Grade::Pass, ITEM, format!("valid for {days} more days")),
+    })
+}
+
+fn letter(score: usize, max: usize) -> &'static str {
+    match score * 100 / max.max(1) {
+        90.. => "A",
+        75..90 => "B",
+        60..75 => "C",
+        40..60 => "D",
+        _ => "F",
+    }
+}
+
+// `url` is the checked URL; `final_url` and `reply` are where redirects ended.
+pub fn scorecard(
+    url: &str,
+    first_redirect: Option<&str>,
+    final_url: &str,
+    reply: &HttpReply,
+    minimal: bool,
+) -> String {
+    let starts_plain = Parser::parse(url).is_ok_and(|u| u.scheme == "http");
+    let https = Parser::parse(final_url).is_ok_and(|u| u.scheme == "https");
+
+    let mut findings = Vec::new();
+    if starts_plain {
+        findings.push(check_https_redirect(first_redirect));
+    }
+    findings.push(check_hsts(reply, https));
+    findings.push(check_csp(reply));
+    findings.push(check_content_type_options(reply));
+    findings.push(check_frame_options(reply));
+    findings.push(check_referrer_policy(reply));
+    findings.extend(check_cookies(reply, https));
+    findings.extend(check_certificate(reply));
+
+    let score: usize = findings
+        .iter()
+        .map(|f| match f.grade {
+            Grade::Pass => 2,
+            Grade::Warn => 1,
+            Grade::Fail => 0,
+        })
+        .sum();
+    let passed = findings.iter().filter(|f| f.grade == Grade::Pass).count();
+    let grade = letter(score, findings.len() * 2);
+    let colored_grade = match grade {
+        "A" => grade.green(),
+        "B" | "C" => grade.yellow(),
+        _ => grade.red(),
+    };
+    let mut out = with_prefix(
+        minimal,
+        &format!(
+            "Security audit of {}: grade {colored_grade} ({passed}/{} passed)",
+            redact_url(final_url),
+            findings.len()
+        ),
+    );
+    let width = findings.iter().map(|f| f.item.len()).max().unwrap_or(0);
+    for f in &findings {
+        let label = match f.grade {
+            Grade::Pass => "pass".green(),
+            Grade::Warn => "warn".yellow(),
+            Grade::Fail => "FAIL".red(),
+        };
+        let _ = write!(out, "\n    {label}  {:width$}  {}", f.item, f.detail);
+    }
+    out
+}
@@ -1,10 +1,10 @@
 use std::env;
 use std::str::FromStr;
 
-pub enum OptionalFlagValue {
+pub enum OptionalFlagValue<T = String> {
     Missing,
     PresentWithoutValue,
-    Present(String),
+    Present(T),
 }
 
 pub struct Arguments {
@@ -80,6 +80,41 @@ impl Arguments {
         OptionalFlagValue::Missing
     }
 
+    pub fn opt_flag_with_optional_value_from_str<T, const N: usize>(
+        &mut self,
+        names: [&str; N],
+    ) -> Result<OptionalFlagValue<T>, String>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        for (i, arg) in self.args.iter().enumerate() {
+            for name in names {
+                if let Some(rest) = arg.strip_prefix(name)
+                    && let Some(value) = rest.strip_prefix('=')
+                {
+                    let value = value
+                        .parse::<T>()
+                        .map_err(|e| format!("Failed to parse value for {name}: {e}"))?;
+                    self.args.remove(i);
+                    return Ok(OptionalFlagValue::Present(value));
+                }
+            }
+        }
+
+        // The value must be attached with '=', so a bare flag never swallows the destination.
+        if let Some(i) = self
+            .args
+            .iter()
+            .position(|arg| names.iter().any(|&name| arg == name))
+        {
+            self.args.remove(i);
+            return Ok(OptionalFlagValue::PresentWithoutValue);
+        }
+
+        Ok(OptionalFlagValue::Missing)
+    }
+
     pub fn opt_value_from_str<T, const N: usize>(
         &mut self,
         names: [&str; N],
@@ -124,4 +159,16 @@ impl Arguments {
 
         Ok(None)
     }
+
+    pub fn values_from_str<T, const N: usize>(&mut self, names: [&str; N]) -> Result<Vec<T>, String>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = self.opt_value_from_str(names)? {
+            values.push(value);
+        }
+        Ok(values)
+    }
 }
@@ -1,3 +1,4 @@
+use crate::https::{TlsOptions, TlsVersion, parse_pin};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -5,12 +6,14 @@ pub struct Config {
     pub minimal: Option<bool>,
     pub no_asn: Option<bool>,
     pub http_headers: Vec<String>,
+    pub tls: TlsOptions,
 }
 
 #[derive(PartialEq)]
 enum Section {
     Settings,
     Headers,
+    Tls,
 }
 
 impl Config {
@@ -29,6 +32,8 @@ impl Config {
         let mut minimal = None;
         let mut no_asn = None;
         let mut http_headers = Vec::new();
+        let mut tls = TlsOptions::default();
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
         let mut section = Section::Headers;
 
         for (i, line) in content.lines().enumerate() {
@@ -44,6 +49,10 @@ impl Config {
                 section = Section::Headers;
                 continue;
             }
+            if line.eq_ignore_ascii_case("[tls]") {
+                section = Section::Tls;
+                continue;
+            }
 
             match section {
                 Section::Settings => {
@@ -66,6 +75,16 @@ impl Config {
                         }
                     }
                 }
+                Section::Tls => {
+                    let (key, value) = line.split_once('=').ok_or_else(|| {
+                        format!(
+                            "Config line {}: expected 'key = value', got: {}",
+                            i + 1,
+                            line
+                        )
+                    })?;
+                    parse_tls_setting(&mut tls, key.trim(), value.trim(), base_dir, i + 1)?;
+                }
                 Section::Headers => {
                     if !line.contains(':') {
                         return Err(format!(
@@ -83,10 +102,46 @@ impl Config {
             minimal,
             no_asn,
             http_headers,
+            tls,
         })
     }
 }
 
+fn parse_tls_setting(
+    tls: &mut TlsOptions,
+    key: &str,
+    value: &str,
+    base_dir: &Path,
+    line: usize,
+) -> Result<(), String> {
+    let version = || {
+        TlsVersion::parse(value)
+            .map(Some)
+            .map_err(|e| format!("Config line {line}: {e}"))
+    };
+    match key {
+        "cacert" => tls.cacert = Some(base_dir.join(value)),
+        "cert" => tls.cert = Some(base_dir.join(value)),
+        "key" => tls.key = Some(base_dir.join(value)),
+        "cert_password" => tls.cert_password = Some(value.to_string()),
+        "insecure" => tls.insecure = Some(parse_bool(value, line)?),
+        "min_version" => tls.min_version = version()?,
+        "max_version" => tls.max_version = version()?,
+        "pin_sha256" => {
+            for pin in value.split(',').filter(|p| !p.trim().is_empty()) {
+                tls.pins
+                    .push(parse_pin(pin).map_err(|e| format!("Config line {line}: {e}"))?);
+            }
+        }
+        unknown => {
+            return Err(format!(
+                "Config line {line}: unknown tls setting '{unknown}'"
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn parse_bool(s: &str, line: usize) -> Result<bool, String> {
     match s {
         "true" | "1" | "yes" => Ok(true),
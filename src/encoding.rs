@@ -0,0 +1,202 @@
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (n >> (18 - 6 * i)) & 0x3f;
+                out.push(char::from(BASE64_ALPHABET[index as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// Standard or URL-safe alphabet. Padding may be left out, but if present it must be complete.
+pub fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
+    let padded = input.trim();
+    let input = padded.trim_end_matches('=');
+    let padding = padded.len() - input.len();
+    if input.len() % 4 == 1 || padding > 2 || (padding > 0 && !padded.len().is_multiple_of(4)) {
+        return Err("Invalid base64 padding".to_string());
+    }
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buf = 0u32;
+    let mut bits = 0;
+    for c in input.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err(format!("Invalid base64 character '{}'", char::from(c))),
+        };
+        buf = buf << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push(((buf >> bits) & 0xff) as u8);
+        }
+    }
+    // Leftover bits must be zero, or the same bytes would have several encodings.
+    if buf & ((1 << bits) - 1) != 0 {
+        return Err("Invalid base64 padding".to_string());
+    }
+    Ok(out)
+}
+
+const PUNY_BASE: u32 = 36;
+const PUNY_TMIN: u32 = 1;
+const PUNY_TMAX: u32 = 26;
+
+const fn puny_adapt(delta: u32, num_points: u32, first: bool) -> u32 {
+    let mut delta = if first { delta / 700 } else { delta / 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((PUNY_BASE - PUNY_TMIN) * PUNY_TMAX) / 2 {
+        delta /= PUNY_BASE - PUNY_TMIN;
+        k += PUNY_BASE;
+    }
+    k + (PUNY_BASE - PUNY_TMIN + 1) * delta / (delta + 38)
+}
+
+fn puny_digit(d: u32) -> char {
+    let d = u8::try_from(d).unwrap_or_default();
+    char::from(if d < 26 { b'a' + d } else { b'0' + d - 26 })
+}
+
+// RFC 3492 Punycode, without the "xn--" prefix.
+#[allow(clippy::many_single_char_names)]
+pub fn punycode_encode(input: &str) -> Option<String> {
+    let code_points: Vec<u32> = input.chars().map(u32::from).collect();
+    let mut out: String = input.chars().filter(char::is_ascii).collect();
+    let basic = u32::try_from(out.len()).ok()?;
+    let mut handled = basic;
+    if basic > 0 {
+        out.push('-');
+    }
+
+    let (mut n, mut delta, mut bias) = (128u32, 0u32, 72u32);
+    while (handled as usize) < code_points.len() {
+        let m = code_points.iter().copied().filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+        for &c in &code_points {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = PUNY_BASE;
+                loop {
+                    let t = if k <= bias {
+                        PUNY_TMIN
+                    } else if k >= bias + PUNY_TMAX {
+                        PUNY_TMAX
+                    } else {
+                        k - bias
+                    };
+                    if q < t {
+                        break;
+                    }
+                    out.push(puny_digit(t + (q - t) % (PUNY_BASE - t)));
+                    q = (q - t) / (PUNY_BASE - t);
+                    k += PUNY_BASE;
+                }
+                out.push(puny_digit(q));
+                bias = puny_adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{base64_decode, base64_encode, punycode_encode};
+
+    // RFC 4648 section 10.
+    const BASE64_VECTORS: [(&str, &str); 7] = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn base64_round_trip() {
+        for (plain, encoded) in BASE64_VECTORS {
+            assert_eq!(base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), plain.as_bytes());
+            assert_eq!(
+                base64_decode(encoded.trim_end_matches('=')).unwrap(),
+                plain.as_bytes()
+            );
+        }
+        assert_eq!(base64_decode("-_8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(base64_decode("+/8=").unwrap(), [0xfb, 0xff]);
+    }
+
+    #[test]
+    fn base64_rejects_bad_padding() {
+        for bad in [
+            "Zg=", "Zg===", "Zm8==", "Zm9v=", "Z", "Zm9vY", "=", "Zh==", "Zm9=",
+        ] {
+            assert!(base64_decode(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn base64_rejects_invalid_characters() {
+        for bad in ["Zm 9v", "Zm9v!", "Zg==Zg==", "Zm\n9v", "Zm9vé"] {
+            assert!(base64_decode(bad).is_err(), "{bad}");
+        }
+    }
+
+    // RFC 3492 section 7.1.
+    #[test]
+    fn punycode_samples() {
+        let samples = [
+            ("ليهمابتكلموشعربي؟", "egbpdaj6bu4bxfgehfvwxn"),
+            ("他们为什么不说中文", "ihqwcrb4cv8a8dqg056pqjye"),
+            ("他們爲什麽不說中文", "ihqwctvzc91f659drss3x8bo0yb"),
+            (
+                "Porquénopuedensimplementehablarenespañol",
+                "Porqunopuedensimplementehablarenespaol-fmd56a",
+            ),
+            ("3年B組金八先生", "3B-ww4c5e180e575a65lsy2b"),
+            (
+                "安室奈美恵-with-SUPER-MONKEYS",
+                "-with-SUPER-MONKEYS-pc58ag80a8qai00g7n9n",
+            ),
+            (
+                "Hello-Another-Way-それぞれの場所",
+                "Hello-Another-Way--fc4qua05auwb3674vfr0b",
+            ),
+            ("そのスピードで", "d9juau41awczczp"),
+            ("-> $1.00 <-", "-> $1.00 <--"),
+        ];
+        for (input, expected) in samples {
+            assert_eq!(punycode_encode(input).as_deref(), Some(expected), "{input}");
+        }
+        assert_eq!(punycode_encode("bücher").as_deref(), Some("bcher-kva"));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::https::HttpReply;
+use crate::regex::Regex;
+use std::time::Duration;
+
+pub enum BodyMatcher {
+    Substring(String),
+    Pattern(Regex),
+}
+
+impl BodyMatcher {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let delimited = input.len() >= 2 && input.starts_with('/');
+        match input.rfind('/') {
+            Some(end) if delimited && end > 0 => {
+                let flags = &input[end + 1..];
+                if !flags.is_empty() && flags != "i" {
+                    return Ok(Self::Substring(input.to_string()));
+                }
+                Regex::with_flags(&input[1..end], flags == "i")
+                    .map(Self::Pattern)
+                    .map_err(|e| format!("Invalid --expect-body pattern '{input}': {e}"))
+            }
+            _ => Ok(Self::Substring(input.to_string())),
+        }
+    }
+
+    pub fn matches(&self, body: &str) -> bool {
+        match self {
+            Self::Substring(needle) => body.contains(needle.as_str()),
+            Self::Pattern(regex) => regex.is_match(body),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Expectations {
+    pub statuses: Vec<(u16, u16)>,
+    pub status_label: String,
+    pub body: Option<(String, BodyMatcher)>,
+    pub headers: Vec<(String, Option<String>)>,
+    pub max_latency: Option<Duration>,
+}
+
+pub fn parse_status_set(input: &str) -> Result<Vec<(u16, u16)>, String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let invalid = || format!("Invalid expected status '{part}'");
+            if let Some(class) = part.strip_suffix("xx").or_else(|| part.strip_suffix("XX")) {
+                let class: u16 = class.parse().map_err(|_| invalid())?;
+                return if (1..=5).contains(&class) {
+                    Ok((class * 100, class * 100 + 99))
+                } else {
+                    Err(invalid())
+                };
+            }
+            let (lo, hi) = part.split_once('-').unwrap_or((part, part));
+            let lo: u16 = lo.trim().parse().map_err(|_| invalid())?;
+            let hi: u16 = hi.trim().parse().map_err(|_| invalid())?;
+            if (100..=599).contains(&lo) && (lo..=599).contains(&hi) {
+                Ok((lo, hi))
+            } else {
+                Err(invalid())
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|set| {
+            if set.is_empty() {
+                Err("Expected status list is empty".to_string())
+            } else {
+                Ok(set)
+            }
+        })
+}
+
+pub fn parse_header_expectation(input: &str) -> (String, Option<String>) {
+    match input.split_once(':') {
+        Some((name, value)) if !value.trim().is_empty() => {
+            (name.trim().to_string(), Some(value.trim().to_string()))
+        }
+        Some((name, _)) => (name.trim().to_string(), None),
+        None => (input.trim().to_string(), None),
+    }
+}
+
+impl Expectations {
+    pub const fn is_empty(&self) -> bool {
+        self.statuses.is_empty()
+            && self.body.is_none()
+            && self.headers.is_empty()
+            && self.max_latency.is_none()
+    }
+
+    pub const fn has_status(&self) -> bool {
+        !self.statuses.is_empty()
+    }
+
+    pub fn status_matches(&self, status: u16) -> bool {
+        self.statuses
+            .iter()
+            .any(|(lo, hi)| (*lo..=*hi).contains(&status))
+    }
+
+    pub fn failures(&self, reply: &HttpReply, elapsed: Duration) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        if self.has_status() && !self.status_matches(reply.status) {
+            failures.push(format!(
+                "expected status {}, got {}",
+                self.status_label, reply.status
+            ));
+        }
+
+        for (name, expected) in &self.headers {
+            match (reply.header(name), expected) {
+                (None, _) => failures.push(format!("missing header {name}")),
+                (Some(actual), Some(expected))
+                    if !actual
+                        .to_ascii_lowercase()
+                        .contains(&expected.to_ascii_lowercase()) =>
+                {
+                    failures.push(format!("expected header {name}: {expected}, got {actual}"));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((label, matcher)) = &self.body
+            && !matcher.matches(&String::from_utf8_lossy(&reply.body))
+        {
+            failures.push(format!("body does not match {label}"));
+        }
+
+        if let Some(max) = self.max_latency
+            && elapsed > max
+        {
+            failures.push(format!(
+                "latency {:.2}ms exceeds {}ms",
+                elapsed.as_secs_f64() * 1000.0,
+                max.as_millis()
+            ));
+        }
+
+        failures
+    }
+}
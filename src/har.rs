@@ -0,0 +1,290 @@
+use crate::http_check::redact_url;
+use crate::https::{HttpReply, HttpVersion, RequestSpec, Timings};
+use crate::parser::{Parser, percent_decode};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Every entry is written in front of this, so the file stays valid JSON after each attempt.
+const TAIL: &str = "\n]}}\n";
+
+pub struct HarLog {
+    file: File,
+    entries: usize,
+}
+
+impl HarLog {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let write = || -> std::io::Result<File> {
+            let mut file = File::create(path)?;
+            let mut head = String::from("{\"log\":{\"version\":\"1.2\",\"creator\":{\"name\":");
+            push_str(&mut head, env!("CARGO_PKG_NAME"));
+            head.push_str(",\"version\":");
+            push_str(&mut head, env!("CARGO_PKG_VERSION"));
+            head.push_str("},\"entries\":[");
+            file.write_all(head.as_bytes())?;
+            file.write_all(TAIL.as_bytes())?;
+            Ok(file)
+        };
+        let file =
+            write().map_err(|e| format!("Failed to create HAR file '{}': {e}", path.display()))?;
+        Ok(Self { file, entries: 0 })
+    }
+
+    pub fn record(
+        &mut self,
+        url: &str,
+        spec: &RequestSpec<'_>,
+        reply: &HttpReply,
+    ) -> std::io::Result<()> {
+        let mut entry = String::new();
+        push_entry_start(&mut entry, reply.timings.total);
+
+        // An HTTP/2 request has no request line, and its pseudo-header names start with ':'.
+        let http2 = reply.protocol == Some(HttpVersion::Http2);
+        let mut lines = reply.request_head.iter().filter(|line| !line.is_empty());
+        let version = if http2 {
+            "HTTP/2.0"
+        } else {
+            lines
+                .next()
+                .and_then(|line| line.rsplit(' ').next())
+                .unwrap_or("HTTP/1.1")
+        };
+        let headers: Vec<(&str, &str)> = lines
+            .filter_map(|line| {
+                let colon = line.get(1..)?.find(':')? + 1;
+                Some((line[..colon].trim(), line[colon + 1..].trim()))
+            })
+            .collect();
+        push_request(&mut entry, url, spec, version, &headers);
+
+        let mime_type = reply.header("Content-Type").unwrap_or_default();
+        let (body_size, compression) = reply.encoding.as_ref().map_or((reply.body.len(), 0), |e| {
+            let saved = if e.decoded {
+                reply.body.len().saturating_sub(e.encoded_len)
+            } else {
+                0
+            };
+            (e.encoded_len, saved)
+        });
+        let _ = write!(
+            entry,
+            ",\"response\":{{\"status\":{},\"statusText\":\"\",\"httpVersion\":\"{version}\",\"cookies\":[],\"headers\":",
+            reply.status
+        );
+        push_pairs(
+            &mut entry,
+            reply
+                .headers
+                .iter()
+                .chain(&reply.trailers)
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        );
+        let _ = write!(
+            entry,
+            ",\"content\":{{\"size\":{},\"compression\":{compression},\"mimeType\":",
+            reply.body.len()
+        );
+        push_str(&mut entry, mime_type);
+        entry.push_str("},\"redirectURL\":");
+        push_str(&mut entry, reply.redirect_location().unwrap_or_default());
+        let _ = write!(entry, ",\"headersSize\":-1,\"bodySize\":{body_size}}}");
+
+        entry.push_str(",\"cache\":{},\"timings\":");
+        push_timings(&mut entry, &reply.timings);
+        if let Some(via) = &reply.via {
+            entry.push_str(",\"_via\":");
+            push_str(&mut entry, via);
+        }
+        entry.push('}');
+        self.append(&entry)
+    }
+
+    // A request that got no response, kept with the error as a custom "_error" field.
+    pub fn record_error(
+        &mut self,
+        url: &str,
+        spec: &RequestSpec<'_>,
+        error: &str,
+    ) -> std::io::Result<()> {
+        let mut entry = String::new();
+        push_entry_start(&mut entry, Duration::ZERO);
+        push_request(&mut entry, url, spec, "HTTP/1.1", &[]);
+        entry.push_str(
+            ",\"response\":{\"status\":0,\"statusText\":\"\",\"httpVersion\":\"\",\"cookies\":[],\"headers\":[],\"content\":{\"size\":0,\"mimeType\":\"\"},\"redirectURL\":\"\",\"headersSize\":-1,\"bodySize\":-1}",
+        );
+        entry.push_str(
+            ",\"cache\":{},\"timings\":{\"send\":0,\"wait\":0,\"receive\":0},\"_error\":",
+        );
+        push_str(&mut entry, error);
+        entry.push('}');
+        self.append(&entry)
+    }
+
+    fn append(&mut self, entry: &str) -> std::io::Result<()> {
+        self.file.seek(SeekFrom::End(
+            -i64::try_from(TAIL.len()).unwrap_or_default(),
+        ))?;
+        let separator = if self.entries == 0 { "\n" } else { ",\n" };
+        self.file
+            .write_all(format!("{separator}{entry}{TAIL}").as_bytes())?;
+        self.entries += 1;
+        Ok(())
+    }
+}
+
+fn push_str(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn push_pairs<'a>(out: &mut String, pairs: impl Iterator<Item = (&'a str, &'a str)>) {
+    out.push('[');
+    for (i, (name, value)) in pairs.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"name\":");
+        push_str(out, name);
+        out.push_str(",\"value\":");
+        push_str(out, value);
+        out.push('}');
+    }
+    out.push(']');
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// Days since 1970-01-01 to a proleptic Gregorian (year, month, day).
+const fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn iso8601(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = i64::try_from(since_epoch.as_secs()).unwrap_or(i64::MAX);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let of_day = secs.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        of_day / 3600,
+        of_day % 3600 / 60,
+        of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+fn push_entry_start(out: &mut String, elapsed: Duration) {
+    let started = SystemTime::now()
+        .checked_sub(elapsed)
+        .unwrap_or_else(SystemTime::now);
+    out.push_str("{\"startedDateTime\":");
+    push_str(out, &iso8601(started));
+    let _ = write!(out, ",\"time\":{:.3}", ms(elapsed));
+}
+
+fn push_request(
+    out: &mut String,
+    url: &str,
+    spec: &RequestSpec<'_>,
+    version: &str,
+    headers: &[(&str, &str)],
+) {
+    out.push_str(",\"request\":{\"method\":");
+    push_str(out, spec.method);
+    out.push_str(",\"url\":");
+    push_str(out, &redact_url(url));
+    out.push_str(",\"httpVersion\":");
+    push_str(out, version);
+    out.push_str(",\"cookies\":[],\"headers\":");
+    push_pairs(out, headers.iter().copied());
+
+    let query = Parser::parse(url).ok().and_then(|url| url.query);
+    let params: Vec<(String, String)> = query
+        .iter()
+        .flat_map(|query| query.split('&'))
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect();
+    out.push_str(",\"queryString\":");
+    push_pairs(
+        out,
+        params
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    );
+
+    if let Some(body) = spec.body {
+        let mime_type = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+            .map_or("", |(_, value)| value);
+        out.push_str(",\"postData\":{\"mimeType\":");
+        push_str(out, mime_type);
+        out.push_str(",\"text\":");
+        push_str(out, &String::from_utf8_lossy(body));
+        out.push('}');
+    }
+    let _ = write!(
+        out,
+        ",\"headersSize\":-1,\"bodySize\":{}}}",
+        spec.body.map_or(0, <[u8]>::len)
+    );
+}
+
+// HAR "connect" includes the proxy handshake and TLS; -1 marks phases that did not happen.
+fn push_timings(out: &mut String, timings: &Timings) {
+    let wait = ms(timings.ttfb);
+    if timings.reused {
+        let receive = (ms(timings.total) - wait).max(0.0);
+        let _ = write!(
+            out,
+            "{{\"blocked\":-1,\"dns\":-1,\"connect\":-1,\"ssl\":-1,\"send\":0,\"wait\":{wait:.3},\"receive\":{receive:.3}}}"
+        );
+        return;
+    }
+    let dns = ms(timings.dns);
+    let connect =
+        ms(timings.connect + timings.proxy.unwrap_or_default() + timings.tls.unwrap_or_default());
+    let ssl = timings.tls.map_or(-1.0, ms);
+    let receive = (ms(timings.total) - dns - connect - wait).max(0.0);
+    let _ = write!(
+        out,
+        "{{\"blocked\":-1,\"dns\":{dns:.3},\"connect\":{connect:.3},\"ssl\":{ssl:.3},\"send\":0,\"wait\":{wait:.3},\"receive\":{receive:.3}}}"
+    );
+}
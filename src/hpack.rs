@@ -0,0 +1,614 @@
+use std::collections::VecDeque;
+
+// RFC 7541 Appendix A.
+const STATIC_TABLE: [(&str, &str); 61] = [
+    (":authority", ""),
+    (":method", "GET"),
+    (":method", "POST"),
+    (":path", "/"),
+    (":path", "/index.html"),
+    (":scheme", "http"),
+    (":scheme", "https"),
+    (":status", "200"),
+    (":status", "204"),
+    (":status", "206"),
+    (":status", "304"),
+    (":status", "400"),
+    (":status", "404"),
+    (":status", "500"),
+    ("accept-charset", ""),
+    ("accept-encoding", "gzip, deflate"),
+    ("accept-language", ""),
+    ("accept-ranges", ""),
+    ("accept", ""),
+    ("access-control-allow-origin", ""),
+    ("age", ""),
+    ("allow", ""),
+    ("authorization", ""),
+    ("cache-control", ""),
+    ("content-disposition", ""),
+    ("content-encoding", ""),
+    ("content-language", ""),
+    ("content-length", ""),
+    ("content-location", ""),
+    ("content-range", ""),
+    ("content-type", ""),
+    ("cookie", ""),
+    ("date", ""),
+    ("etag", ""),
+    ("expect", ""),
+    ("expires", ""),
+    ("from", ""),
+    ("host", ""),
+    ("if-match", ""),
+    ("if-modified-since", ""),
+    ("if-none-match", ""),
+    ("if-range", ""),
+    ("if-unmodified-since", ""),
+    ("last-modified", ""),
+    ("link", ""),
+    ("location", ""),
+    ("max-forwards", ""),
+    ("proxy-authenticate", ""),
+    ("proxy-authorization", ""),
+    ("range", ""),
+    ("referer", ""),
+    ("refresh", ""),
+    ("retry-after", ""),
+    ("server", ""),
+    ("set-cookie", ""),
+    ("strict-transport-security", ""),
+    ("transfer-encoding", ""),
+    ("user-agent", ""),
+    ("vary", ""),
+    ("via", ""),
+    ("www-authenticate", ""),
+];
+
+// Code lengths of the canonical Huffman code in RFC 7541 Appendix B; symbol 256 is EOS.
+const HUFFMAN_LENGTHS: [u8; 257] = [
+    13, 23, 28, 28, 28, 28, 28, 28, 28, 24, 30, 28, 28, 30, 28, 28, 28, 28, 28, 28, 28, 28, 30, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 6, 10, 10, 12, 13, 6, 8, 11, 10, 10, 8, 11, 8, 6, 6, 6, 5, 5,
+    5, 6, 6, 6, 6, 6, 6, 6, 7, 8, 15, 6, 12, 10, 13, 6, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+    7, 7, 7, 7, 7, 7, 7, 7, 8, 7, 8, 13, 19, 13, 14, 6, 15, 5, 6, 5, 6, 5, 6, 6, 6, 5, 7, 7, 6, 6,
+    6, 5, 6, 7, 6, 5, 5, 6, 7, 7, 7, 7, 7, 15, 11, 14, 13, 28, 20, 22, 20, 20, 22, 22, 22, 23, 22,
+    23, 23, 23, 23, 23, 24, 23, 24, 24, 22, 23, 24, 23, 23, 23, 23, 21, 22, 23, 22, 23, 23, 24, 22,
+    21, 20, 22, 22, 23, 23, 21, 23, 22, 22, 24, 21, 22, 23, 23, 21, 21, 22, 21, 23, 22, 23, 23, 20,
+    22, 22, 22, 23, 22, 22, 23, 26, 26, 20, 19, 22, 23, 22, 25, 26, 26, 26, 27, 27, 26, 24, 25, 19,
+    21, 26, 27, 27, 26, 27, 24, 21, 21, 26, 26, 28, 27, 27, 27, 20, 24, 20, 21, 22, 21, 21, 23, 22,
+    22, 25, 25, 24, 24, 26, 23, 26, 27, 26, 26, 27, 27, 27, 27, 27, 28, 27, 27, 27, 27, 27, 26, 30,
+];
+const HUFFMAN_MAX_BITS: usize = 30;
+const DEFAULT_TABLE_SIZE: usize = 4096;
+const MAX_STRING: usize = 64 * 1024;
+
+fn push_int(out: &mut Vec<u8>, prefix_bits: u32, flags: u8, value: usize) {
+    let max = (1usize << prefix_bits) - 1;
+    if value < max {
+        out.push(flags | u8::try_from(value).unwrap_or_default());
+        return;
+    }
+    out.push(flags | u8::try_from(max).unwrap_or_default());
+    let mut rest = value - max;
+    while rest >= 0x80 {
+        out.push(u8::try_from(rest & 0x7f).unwrap_or_default() | 0x80);
+        rest >>= 7;
+    }
+    out.push(u8::try_from(rest).unwrap_or_default());
+}
+
+fn push_string(out: &mut Vec<u8>, value: &str) {
+    push_int(out, 7, 0, value.len());
+    out.extend_from_slice(value.as_bytes());
+}
+
+// Encodes every field as a literal that is never added to the peer's dynamic table,
+// so requests never depend on compression state.
+pub fn encode(fields: &[(String, String)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (name, value) in fields {
+        out.push(0);
+        push_string(&mut out, name);
+        push_string(&mut out, value);
+    }
+    out
+}
+
+// Number of codes of each length, and the symbols in canonical code order: by length, then
+// by symbol. Built at compile time.
+const HUFFMAN_COUNTS: [usize; HUFFMAN_MAX_BITS + 1] = {
+    let mut counts = [0usize; HUFFMAN_MAX_BITS + 1];
+    let mut symbol = 0;
+    while symbol < HUFFMAN_LENGTHS.len() {
+        counts[HUFFMAN_LENGTHS[symbol] as usize] += 1;
+        symbol += 1;
+    }
+    counts
+};
+const HUFFMAN_SYMBOLS: [usize; 257] = {
+    let mut symbols = [0usize; 257];
+    let mut next = 0;
+    let mut len = 1;
+    while len <= HUFFMAN_MAX_BITS {
+        let mut symbol = 0;
+        while symbol < HUFFMAN_LENGTHS.len() {
+            if HUFFMAN_LENGTHS[symbol] as usize == len {
+                symbols[next] = symbol;
+                next += 1;
+            }
+            symbol += 1;
+        }
+        len += 1;
+    }
+    symbols
+};
+
+fn huffman_decode(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(data.len() * 8 / 5);
+    let (mut code, mut len, mut first, mut index) = (0usize, 0usize, 0usize, 0usize);
+    for byte in data {
+        for shift in (0..8).rev() {
+            code = (code << 1) | usize::from((byte >> shift) & 1);
+            len += 1;
+            if code < first + HUFFMAN_COUNTS[len] {
+                let symbol = HUFFMAN_SYMBOLS[index + code - first];
+                if symbol == 256 {
+                    return Err("HPACK string contains the EOS symbol".to_string());
+                }
+                out.push(u8::try_from(symbol).unwrap_or_default());
+                (code, len, first, index) = (0, 0, 0, 0);
+            } else if len == HUFFMAN_MAX_BITS {
+                return Err("Invalid HPACK Huffman code".to_string());
+            } else {
+                index += HUFFMAN_COUNTS[len];
+                first = (first + HUFFMAN_COUNTS[len]) << 1;
+            }
+        }
+    }
+    // Leftover bits must be a prefix of EOS, which is all ones.
+    if len > 7 || code != (1 << len) - 1 {
+        return Err("Invalid HPACK Huffman padding".to_string());
+    }
+    Ok(out)
+}
+
+struct Input<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Input<'_> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .data
+            .get(self.pos)
+            .ok_or("HPACK header block is truncated")?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn int(&mut self, first: u8, prefix_bits: u32) -> Result<usize, String> {
+        let max = (1usize << prefix_bits) - 1;
+        let mut value = usize::from(first) & max;
+        if value < max {
+            return Ok(value);
+        }
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift > 28 {
+                return Err("HPACK integer is too large".to_string());
+            }
+            value += usize::from(byte & 0x7f) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let first = self.byte()?;
+        let len = self.int(first, 7)?;
+        if len > MAX_STRING {
+            return Err("HPACK string is too long".to_string());
+        }
+        let raw = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or("HPACK header block is truncated")?;
+        self.pos += len;
+        let bytes = if first & 0x80 == 0 {
+            raw.to_vec()
+        } else {
+            huffman_decode(raw)?
+        };
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+pub struct Decoder {
+    // Newest entry first, as HPACK indexes it.
+    dynamic: VecDeque<(String, String)>,
+    size: usize,
+    max_size: usize,
+}
+
+impl Decoder {
+    pub const fn new() -> Self {
+        Self {
+            dynamic: VecDeque::new(),
+            size: 0,
+            max_size: DEFAULT_TABLE_SIZE,
+        }
+    }
+
+    fn entry(&self, index: usize) -> Result<(String, String), String> {
+        let entry = match index {
+            0 => None,
+            1..=61 => STATIC_TABLE
+                .get(index - 1)
+                .map(|(name, value)| ((*name).to_string(), (*value).to_string())),
+            _ => self.dynamic.get(index - 62).cloned(),
+        };
+        entry.ok_or_else(|| format!("Invalid HPACK table index {index}"))
+    }
+
+    fn evict(&mut self, max_size: usize) {
+        while self.size > max_size {
+            let Some((name, value)) = self.dynamic.pop_back() else {
+                break;
+            };
+            self.size -= name.len() + value.len() + 32;
+        }
+    }
+
+    fn insert(&mut self, name: &str, value: &str) {
+        let size = name.len() + value.len() + 32;
+        self.evict(self.max_size.saturating_sub(size));
+        if size <= self.max_size {
+            self.dynamic
+                .push_front((name.to_string(), value.to_string()));
+            self.size += size;
+        }
+    }
+
+    pub fn decode(&mut self, block: &[u8]) -> Result<Vec<(String, String)>, String> {
+        let mut input = Input {
+            data: block,
+            pos: 0,
+        };
+        let mut fields = Vec::new();
+        while input.pos < block.len() {
+            let first = input.byte()?;
+            if first & 0x80 != 0 {
+                fields.push(self.entry(input.int(first, 7)?)?);
+            } else if first & 0xe0 == 0x20 {
+                let max_size = input.int(first, 5)?;
+                if max_size > DEFAULT_TABLE_SIZE {
+                    return Err("HPACK table size update above the advertised limit".to_string());
+                }
+                self.max_size = max_size;
+                self.evict(max_size);
+            } else {
+                // Literal with incremental indexing (6-bit index) or without (4-bit index).
+                let indexed = first & 0x40 != 0;
+                let index = input.int(first, if indexed { 6 } else { 4 })?;
+                let name = if index == 0 {
+                    input.string()?
+                } else {
+                    self.entry(index)?.0
+                };
+                let value = input.string()?;
+                if indexed {
+                    self.insert(&name, &value);
+                }
+                fields.push((name, value));
+            }
+        }
+        Ok(fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decoder, Input, encode, huffman_decode, push_int};
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        let digits: Vec<u8> = hex.bytes().filter(u8::is_ascii_hexdigit).collect();
+        digits
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+            .collect()
+    }
+
+    fn fields(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    fn table(decoder: &Decoder) -> Vec<(String, String)> {
+        decoder.dynamic.iter().cloned().collect()
+    }
+
+    type Fields = &'static [(&'static str, &'static str)];
+
+    // Decodes each block in turn, checking the fields, the dynamic table and its size.
+    fn check_sequence(decoder: &mut Decoder, steps: &[(&str, Fields, Fields, usize)]) {
+        for (i, (block, expected, dynamic, size)) in steps.iter().enumerate() {
+            assert_eq!(
+                decoder.decode(&unhex(block)).unwrap(),
+                fields(expected),
+                "block {i}"
+            );
+            assert_eq!(table(decoder), fields(dynamic), "table after block {i}");
+            assert_eq!(decoder.size, *size, "table size after block {i}");
+        }
+    }
+
+    // C.1
+    #[test]
+    fn integers() {
+        let cases: [(usize, u32, &str); 3] = [(10, 5, "0a"), (1337, 5, "1f9a0a"), (42, 8, "2a")];
+        for (value, prefix_bits, hex) in cases {
+            let mut out = Vec::new();
+            push_int(&mut out, prefix_bits, 0, value);
+            assert_eq!(out, unhex(hex), "{value}");
+            let mut input = Input {
+                data: &out[1..],
+                pos: 0,
+            };
+            assert_eq!(input.int(out[0], prefix_bits).unwrap(), value);
+        }
+    }
+
+    // C.2
+    #[test]
+    fn header_field_representations() {
+        let mut decoder = Decoder::new();
+        check_sequence(
+            &mut decoder,
+            &[(
+                "400a 6375 7374 6f6d 2d6b 6579 0d63 7573 746f 6d2d 6865 6164 6572",
+                &[("custom-key", "custom-header")],
+                &[("custom-key", "custom-header")],
+                55,
+            )],
+        );
+
+        for (block, name, value) in [
+            (
+                "040c 2f73 616d 706c 652f 7061 7468",
+                ":path",
+                "/sample/path",
+            ),
+            (
+                "1008 7061 7373 776f 7264 0673 6563 7265 74",
+                "password",
+                "secret",
+            ),
+            ("82", ":method", "GET"),
+        ] {
+            let mut decoder = Decoder::new();
+            assert_eq!(
+                decoder.decode(&unhex(block)).unwrap(),
+                fields(&[(name, value)])
+            );
+            assert!(decoder.dynamic.is_empty());
+        }
+    }
+
+    const REQUEST_1: Fields = &[
+        (":method", "GET"),
+        (":scheme", "http"),
+        (":path", "/"),
+        (":authority", "www.example.com"),
+    ];
+    const REQUEST_2: Fields = &[
+        (":method", "GET"),
+        (":scheme", "http"),
+        (":path", "/"),
+        (":authority", "www.example.com"),
+        ("cache-control", "no-cache"),
+    ];
+    const REQUEST_3: Fields = &[
+        (":method", "GET"),
+        (":scheme", "https"),
+        (":path", "/index.html"),
+        (":authority", "www.example.com"),
+        ("custom-key", "custom-value"),
+    ];
+    const REQUEST_TABLE_1: Fields = &[(":authority", "www.example.com")];
+    const REQUEST_TABLE_2: Fields = &[
+        ("cache-control", "no-cache"),
+        (":authority", "www.example.com"),
+    ];
+    const REQUEST_TABLE_3: Fields = &[
+        ("custom-key", "custom-value"),
+        ("cache-control", "no-cache"),
+        (":authority", "www.example.com"),
+    ];
+
+    // C.3
+    #[test]
+    fn requests_without_huffman() {
+        check_sequence(
+            &mut Decoder::new(),
+            &[
+                (
+                    "8286 8441 0f77 7777 2e65 7861 6d70 6c65 2e63 6f6d",
+                    REQUEST_1,
+                    REQUEST_TABLE_1,
+                    57,
+                ),
+                (
+                    "8286 84be 5808 6e6f 2d63 6163 6865",
+                    REQUEST_2,
+                    REQUEST_TABLE_2,
+                    110,
+                ),
+                (
+                    "8287 85bf 400a 6375 7374 6f6d 2d6b 6579 0c63 7573 746f 6d2d 7661 6c75 65",
+                    REQUEST_3,
+                    REQUEST_TABLE_3,
+                    164,
+                ),
+            ],
+        );
+    }
+
+    // C.4
+    #[test]
+    fn requests_with_huffman() {
+        check_sequence(
+            &mut Decoder::new(),
+            &[
+                (
+                    "8286 8441 8cf1 e3c2 e5f2 3a6b a0ab 90f4 ff",
+                    REQUEST_1,
+                    REQUEST_TABLE_1,
+                    57,
+                ),
+                (
+                    "8286 84be 5886 a8eb 1064 9cbf",
+                    REQUEST_2,
+                    REQUEST_TABLE_2,
+                    110,
+                ),
+                (
+                    "8287 85bf 4088 25a8 49e9 5ba9 7d7f 8925 a849 e95b b8e8 b4bf",
+                    REQUEST_3,
+                    REQUEST_TABLE_3,
+                    164,
+                ),
+            ],
+        );
+    }
+
+    const RESPONSE_1: Fields = &[
+        (":status", "302"),
+        ("cache-control", "private"),
+        ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+        ("location", "https://www.example.com"),
+    ];
+    const RESPONSE_TABLE_1: Fields = &[
+        ("location", "https://www.example.com"),
+        ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+        ("cache-control", "private"),
+        (":status", "302"),
+    ];
+    const RESPONSE_2: Fields = &[
+        (":status", "307"),
+        ("cache-control", "private"),
+        ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+        ("location", "https://www.example.com"),
+    ];
+    const RESPONSE_TABLE_2: Fields = &[
+        (":status", "307"),
+        ("location", "https://www.example.com"),
+        ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+        ("cache-control", "private"),
+    ];
+    const RESPONSE_3: Fields = &[
+        (":status", "200"),
+        ("cache-control", "private"),
+        ("date", "Mon, 21 Oct 2013 20:13:22 GMT"),
+        ("location", "https://www.example.com"),
+        ("content-encoding", "gzip"),
+        (
+            "set-cookie",
+            "foo=ASDJKHQKBZXOQWEOPIUAXQWEOIU; max-age=3600; version=1",
+        ),
+    ];
+    const RESPONSE_TABLE_3: Fields = &[
+        (
+            "set-cookie",
+            "foo=ASDJKHQKBZXOQWEOPIUAXQWEOIU; max-age=3600; version=1",
+        ),
+        ("content-encoding", "gzip"),
+        ("date", "Mon, 21 Oct 2013 20:13:22 GMT"),
+    ];
+
+    // The response examples run with SETTINGS_HEADER_TABLE_SIZE = 256, so entries get evicted.
+    fn response_decoder() -> Decoder {
+        let mut decoder = Decoder::new();
+        decoder.max_size = 256;
+        decoder
+    }
+
+    // C.5
+    #[test]
+    fn responses_without_huffman() {
+        check_sequence(
+            &mut response_decoder(),
+            &[
+                (
+                    "4803 3330 3258 0770 7269 7661 7465 611d 4d6f 6e2c 2032 3120 4f63 7420 3230
+                     3133 2032 303a 3133 3a32 3120 474d 546e 1768 7474 7073 3a2f 2f77 7777 2e65
+                     7861 6d70 6c65 2e63 6f6d",
+                    RESPONSE_1,
+                    RESPONSE_TABLE_1,
+                    222,
+                ),
+                ("4803 3330 37c1 c0bf", RESPONSE_2, RESPONSE_TABLE_2, 222),
+                (
+                    "88c1 611d 4d6f 6e2c 2032 3120 4f63 7420 3230 3133 2032 303a 3133 3a32 3220
+                     474d 54c0 5a04 677a 6970 7738 666f 6f3d 4153 444a 4b48 514b 425a 584f 5157
+                     454f 5049 5541 5851 5745 4f49 553b 206d 6178 2d61 6765 3d33 3630 303b 2076
+                     6572 7369 6f6e 3d31",
+                    RESPONSE_3,
+                    RESPONSE_TABLE_3,
+                    215,
+                ),
+            ],
+        );
+    }
+
+    // C.6
+    #[test]
+    fn responses_with_huffman() {
+        check_sequence(
+            &mut response_decoder(),
+            &[
+                (
+                    "4882 6402 5885 aec3 771a 4b61 96d0 7abe 9410 54d4 44a8 2005 9504 0b81 66e0
+                     82a6 2d1b ff6e 919d 29ad 1718 63c7 8f0b 97c8 e9ae 82ae 43d3",
+                    RESPONSE_1,
+                    RESPONSE_TABLE_1,
+                    222,
+                ),
+                ("4883 640e ffc1 c0bf", RESPONSE_2, RESPONSE_TABLE_2, 222),
+                (
+                    "88c1 6196 d07a be94 1054 d444 a820 0595 040b 8166 e084 a62d 1bff c05a 839b
+                     d9ab 77ad 94e7 821d d7f2 e6c7 b335 dfdf cd5b 3960 d5af 2708 7f36 72c1 ab27
+                     0fb5 291f 9587 3160 65c0 03ed 4ee5 b106 3d50 07",
+                    RESPONSE_3,
+                    RESPONSE_TABLE_3,
+                    215,
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn huffman_errors() {
+        assert_eq!(
+            huffman_decode(&unhex("f1e3c2e5f23a6ba0ab90f4ff")).unwrap(),
+            b"www.example.com"
+        );
+        // Padding longer than 7 bits, padding that isn't all ones, and an explicit EOS.
+        assert!(huffman_decode(&unhex("1fff")).is_err());
+        assert!(huffman_decode(&unhex("18")).is_err());
+        assert!(huffman_decode(&unhex("ffffffff")).is_err());
+    }
+
+    #[test]
+    fn encoded_fields_round_trip() {
+        let list = fields(&[(":method", "GET"), ("user-agent", "meowping")]);
+        let block = encode(&list);
+        let mut decoder = Decoder::new();
+        assert_eq!(decoder.decode(&block).unwrap(), list);
+        assert!(decoder.dynamic.is_empty());
+    }
+}
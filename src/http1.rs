@@ -0,0 +1,186 @@
+use std::error::Error;
+use std::io::{BufRead, Read};
+
+const MAX_LINE: u64 = 64 * 1024;
+const MAX_HEADERS: usize = 256;
+
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub trailers: Vec<(String, String)>,
+    pub keep_alive: bool,
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn has_token(headers: &[(String, String)], name: &str, token: &str) -> bool {
+    headers
+        .iter()
+        .filter(|(header, _)| header.eq_ignore_ascii_case(name))
+        .flat_map(|(_, value)| value.split(','))
+        .any(|t| t.trim().eq_ignore_ascii_case(token))
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<Option<String>, Box<dyn Error>> {
+    let mut line = Vec::new();
+    let n = reader.take(MAX_LINE).read_until(b'\n', &mut line)?;
+    if n == 0 {
+        return Ok(None);
+    }
+    if line.last() != Some(&b'\n') {
+        return Err("Response line too long or truncated".into());
+    }
+    line.pop();
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
+
+fn read_headers(reader: &mut impl BufRead) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    loop {
+        let line = read_line(reader)?.ok_or("Connection closed inside response headers")?;
+        if line.is_empty() {
+            return Ok(headers);
+        }
+        if line.starts_with([' ', '\t']) {
+            let (_, value) = headers.last_mut().ok_or("Malformed header continuation")?;
+            value.push(' ');
+            value.push_str(line.trim());
+            continue;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Malformed header line: {line}"))?;
+        if headers.len() >= MAX_HEADERS {
+            return Err("Too many response headers".into());
+        }
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+}
+
+fn parse_status_line(line: &str) -> Result<(String, u16), Box<dyn Error>> {
+    let mut parts = line.splitn(3, ' ');
+    let version = parts.next().unwrap_or_default();
+    if !version.starts_with("HTTP/") {
+        return Err(format!("Invalid HTTP status line: {line}").into());
+    }
+    let status = parts
+        .next()
+        .and_then(|code| code.parse::<u16>().ok())
+        .filter(|code| (100..=999).contains(code))
+        .ok_or("Failed to parse status code")?;
+    Ok((version.to_string(), status))
+}
+
+fn read_exact_body(
+    reader: &mut impl BufRead,
+    len: u64,
+    body: &mut Vec<u8>,
+) -> Result<(), Box<dyn Error>> {
+    let read = reader.take(len).read_to_end(body)?;
+    if (read as u64) < len {
+        return Err(format!("Connection closed after {read} of {len} body bytes").into());
+    }
+    Ok(())
+}
+
+fn read_chunked(
+    reader: &mut impl BufRead,
+    body: &mut Vec<u8>,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    loop {
+        let line = read_line(reader)?.ok_or("Connection closed inside chunked body")?;
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size =
+            u64::from_str_radix(size, 16).map_err(|_| format!("Invalid chunk size: {line}"))?;
+        if size == 0 {
+            return read_headers(reader);
+        }
+        read_exact_body(reader, size, body)?;
+        if read_line(reader)?.is_none_or(|end| !end.is_empty()) {
+            return Err("Missing CRLF after chunk data".into());
+        }
+    }
+}
+
+fn content_length(headers: &[(String, String)]) -> Result<Option<u64>, Box<dyn Error>> {
+    let mut lengths = headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        .flat_map(|(_, value)| value.split(','))
+        .map(|v| v.trim().parse::<u64>());
+    let Some(first) = lengths.next() else {
+        return Ok(None);
+    };
+    let first = first.map_err(|_| "Invalid Content-Length")?;
+    if lengths.any(|other| other.ok() != Some(first)) {
+        return Err("Conflicting Content-Length headers".into());
+    }
+    Ok(Some(first))
+}
+
+// Reads only a status line and headers, e.g. the reply to a proxy CONNECT.
+pub fn read_head(reader: &mut impl BufRead) -> Result<u16, Box<dyn Error>> {
+    let line = read_line(reader)?.ok_or("Connection closed before a response was received")?;
+    let (_, status) = parse_status_line(&line)?;
+    read_headers(reader)?;
+    Ok(status)
+}
+
+pub fn read_response(
+    reader: &mut impl BufRead,
+    head_request: bool,
+) -> Result<Response, Box<dyn Error>> {
+    let (version, status, headers) = loop {
+        let line = read_line(reader)?.ok_or("Connection closed before a response was received")?;
+        let (version, status) = parse_status_line(&line)?;
+        let headers = read_headers(reader)?;
+        if !(100..200).contains(&status) || status == 101 {
+            break (version, status, headers);
+        }
+    };
+
+    let mut keep_alive = if version == "HTTP/1.0" {
+        has_token(&headers, "Connection", "keep-alive")
+    } else {
+        !has_token(&headers, "Connection", "close")
+    };
+
+    let mut body = Vec::new();
+    let mut trailers = Vec::new();
+    let no_body = head_request || (100..200).contains(&status) || status == 204 || status == 304;
+    if !no_body {
+        let chunked = header(&headers, "Transfer-Encoding").is_some_and(|codings| {
+            codings
+                .rsplit(',')
+                .next()
+                .is_some_and(|last| last.trim().eq_ignore_ascii_case("chunked"))
+        });
+        if chunked {
+            trailers = read_chunked(reader, &mut body)?;
+        } else if header(&headers, "Transfer-Encoding").is_none()
+            && let Some(len) = content_length(&headers)?
+        {
+            read_exact_body(reader, len, &mut body)?;
+        } else {
+            reader.read_to_end(&mut body)?;
+            keep_alive = false;
+        }
+    }
+
+    Ok(Response {
+        status,
+        headers,
+        body,
+        trailers,
+        keep_alive,
+    })
+}
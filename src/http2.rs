@@ -0,0 +1,494 @@
+use crate::hpack;
+use crate::http1::Response;
+use std::error::Error;
+use std::io::{ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+const DATA: u8 = 0x0;
+const HEADERS: u8 = 0x1;
+const RST_STREAM: u8 = 0x3;
+const SETTINGS: u8 = 0x4;
+const PUSH_PROMISE: u8 = 0x5;
+const PING: u8 = 0x6;
+const GOAWAY: u8 = 0x7;
+const WINDOW_UPDATE: u8 = 0x8;
+const CONTINUATION: u8 = 0x9;
+
+const END_STREAM: u8 = 0x1;
+const ACK: u8 = 0x1;
+const END_HEADERS: u8 = 0x4;
+const PADDED: u8 = 0x8;
+const PRIORITY: u8 = 0x20;
+
+const SETTINGS_ENABLE_PUSH: u16 = 0x2;
+const SETTINGS_INITIAL_WINDOW_SIZE: u16 = 0x4;
+const SETTINGS_MAX_FRAME_SIZE: u16 = 0x5;
+
+// Client streams are odd; the highest id leaves room for the final +2 without overflow.
+const LAST_STREAM: u32 = 0x7fff_fffd;
+const DEFAULT_WINDOW: i64 = 65_535;
+const DEFAULT_MAX_FRAME: usize = 16_384;
+// Large enough that a response never waits for a WINDOW_UPDATE from us; the connection
+// window is topped up again after every response.
+const RECEIVE_WINDOW: u32 = 1 << 30;
+const MAX_BODY: usize = 64 * 1024 * 1024;
+
+const ERROR_CODES: [&str; 14] = [
+    "NO_ERROR",
+    "PROTOCOL_ERROR",
+    "INTERNAL_ERROR",
+    "FLOW_CONTROL_ERROR",
+    "SETTINGS_TIMEOUT",
+    "STREAM_CLOSED",
+    "FRAME_SIZE_ERROR",
+    "REFUSED_STREAM",
+    "CANCEL",
+    "COMPRESSION_ERROR",
+    "CONNECT_ERROR",
+    "ENHANCE_YOUR_CALM",
+    "INADEQUATE_SECURITY",
+    "HTTP_1_1_REQUIRED",
+];
+
+// Headers that only mean something to an HTTP/1.1 connection and are forbidden in HTTP/2.
+const CONNECTION_HEADERS: &[&str] = &[
+    "host",
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+];
+
+struct Frame {
+    kind: u8,
+    flags: u8,
+    stream: u32,
+    payload: Vec<u8>,
+}
+
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    data.get(pos..pos + 4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map_or(0, u32::from_be_bytes)
+}
+
+fn error_name(data: &[u8], pos: usize) -> String {
+    let code = u32_at(data, pos);
+    usize::try_from(code)
+        .ok()
+        .and_then(|code| ERROR_CODES.get(code))
+        .map_or_else(|| format!("error {code}"), |name| (*name).to_string())
+}
+
+fn encode_frame(kind: u8, flags: u8, stream: u32, payload: &[u8]) -> Vec<u8> {
+    let len = u32::try_from(payload.len())
+        .unwrap_or_default()
+        .to_be_bytes();
+    let mut out = Vec::with_capacity(9 + payload.len());
+    out.extend_from_slice(&len[1..]);
+    out.push(kind);
+    out.push(flags);
+    out.extend_from_slice(&stream.to_be_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+fn closed_inside(e: std::io::Error) -> Box<dyn Error> {
+    if e.kind() == ErrorKind::UnexpectedEof {
+        "Connection closed inside the HTTP/2 response".into()
+    } else {
+        e.into()
+    }
+}
+
+// Reads the next frame, or None if the server closed the connection between two frames.
+fn next_frame(conn: &mut impl Read) -> Result<Option<Frame>, Box<dyn Error>> {
+    let mut header = [0u8; 9];
+    loop {
+        match conn.read(&mut header[..1]) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    conn.read_exact(&mut header[1..]).map_err(closed_inside)?;
+    let len = usize::try_from(u32::from_be_bytes([0, header[0], header[1], header[2]]))?;
+    // We never raise SETTINGS_MAX_FRAME_SIZE, so anything bigger is a broken server.
+    if len > DEFAULT_MAX_FRAME {
+        return Err(format!("HTTP/2 frame of {len} bytes exceeds the frame size limit").into());
+    }
+    let mut payload = vec![0u8; len];
+    conn.read_exact(&mut payload).map_err(closed_inside)?;
+    Ok(Some(Frame {
+        kind: header[3],
+        flags: header[4],
+        stream: u32_at(&header, 5) & 0x7fff_ffff,
+        payload,
+    }))
+}
+
+fn read_frame(conn: &mut impl Read) -> Result<Frame, Box<dyn Error>> {
+    next_frame(conn)?.ok_or_else(|| "Connection closed inside the HTTP/2 response".into())
+}
+
+// Strips padding (and the priority block of a HEADERS frame) from a frame payload.
+fn frame_content(frame: &Frame) -> Result<&[u8], Box<dyn Error>> {
+    let mut content = frame.payload.as_slice();
+    if frame.flags & PADDED != 0 {
+        let (&pad, rest) = content
+            .split_first()
+            .ok_or("Malformed padded HTTP/2 frame")?;
+        let len = rest
+            .len()
+            .checked_sub(usize::from(pad))
+            .ok_or("HTTP/2 frame padding exceeds the frame")?;
+        content = &rest[..len];
+    }
+    if frame.kind == HEADERS && frame.flags & PRIORITY != 0 {
+        content = content
+            .get(5..)
+            .ok_or("Malformed HTTP/2 HEADERS priority")?;
+    }
+    Ok(content)
+}
+
+// Turns the HTTP/1.1 request head we already build into HTTP/2 pseudo-headers and fields.
+pub fn request_fields(head: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut lines = head.lines().filter(|line| !line.is_empty());
+    let request_line = lines.next().ok_or("Empty request head")?;
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(format!("Malformed request line: {request_line}").into());
+    };
+
+    let mut authority = String::new();
+    let mut fields = Vec::new();
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim().to_ascii_lowercase();
+        let value = value.trim().to_string();
+        if name == "host" {
+            authority = value;
+        } else if !CONNECTION_HEADERS.contains(&name.as_str()) {
+            fields.push((name, value));
+        }
+    }
+    let mut pseudo = vec![
+        (":method".to_string(), method.to_string()),
+        (":scheme".to_string(), "https".to_string()),
+        (":authority".to_string(), authority),
+        (":path".to_string(), target.to_string()),
+    ];
+    pseudo.append(&mut fields);
+    Ok(pseudo)
+}
+
+// State of a kept-alive HTTP/2 connection that carries over from one request to the next.
+pub struct Session {
+    decoder: hpack::Decoder,
+    next_stream: u32,
+    connection_window: i64,
+    initial_window: i64,
+    max_frame: usize,
+    // Set by GOAWAY: the connection must not be used for new streams.
+    closing: bool,
+}
+
+impl Session {
+    pub const fn new() -> Self {
+        Self {
+            decoder: hpack::Decoder::new(),
+            next_stream: 1,
+            connection_window: DEFAULT_WINDOW,
+            initial_window: DEFAULT_WINDOW,
+            max_frame: DEFAULT_MAX_FRAME,
+            closing: false,
+        }
+    }
+
+    // Allocates the next stream id, sending the connection preface before the first one.
+    // None once the connection may not open more streams.
+    fn open_stream(&mut self, conn: &mut impl Write) -> std::io::Result<Option<u32>> {
+        if self.closing || self.next_stream > LAST_STREAM {
+            return Ok(None);
+        }
+        if self.next_stream == 1 {
+            send_preface(conn)?;
+        }
+        let stream = self.next_stream;
+        self.next_stream += 2;
+        Ok(Some(stream))
+    }
+
+    // Returns how much the window of every open stream changes.
+    fn apply_settings(&mut self, payload: &[u8]) -> i64 {
+        let mut delta = 0;
+        for setting in payload.as_chunks::<6>().0 {
+            let value = u32_at(setting, 2);
+            match u16::from_be_bytes([setting[0], setting[1]]) {
+                SETTINGS_INITIAL_WINDOW_SIZE => {
+                    delta += i64::from(value) - self.initial_window;
+                    self.initial_window = i64::from(value);
+                }
+                SETTINGS_MAX_FRAME_SIZE => {
+                    self.max_frame = usize::try_from(value)
+                        .unwrap_or(DEFAULT_MAX_FRAME)
+                        .max(DEFAULT_MAX_FRAME);
+                }
+                _ => {}
+            }
+        }
+        delta
+    }
+}
+
+// The request body, sent as far as the server's flow control windows allow.
+struct Upload<'a> {
+    data: &'a [u8],
+    done: bool,
+    stream: u32,
+    stream_window: i64,
+}
+
+impl Upload<'_> {
+    fn send(&mut self, conn: &mut impl Write, session: &mut Session) -> std::io::Result<()> {
+        while !self.done {
+            let window = session.connection_window.min(self.stream_window).max(0);
+            let n = self
+                .data
+                .len()
+                .min(session.max_frame)
+                .min(usize::try_from(window).unwrap_or(usize::MAX));
+            if n == 0 && !self.data.is_empty() {
+                return Ok(());
+            }
+            let (chunk, rest) = self.data.split_at(n);
+            let flags = if rest.is_empty() { END_STREAM } else { 0 };
+            conn.write_all(&encode_frame(DATA, flags, self.stream, chunk))?;
+            let sent = i64::try_from(n).unwrap_or(i64::MAX);
+            session.connection_window -= sent;
+            self.stream_window -= sent;
+            self.data = rest;
+            self.done = rest.is_empty();
+        }
+        Ok(())
+    }
+}
+
+fn send_headers(
+    conn: &mut impl Write,
+    stream: u32,
+    block: &[u8],
+    end_stream: bool,
+) -> std::io::Result<()> {
+    let mut chunks = block.chunks(DEFAULT_MAX_FRAME).peekable();
+    let mut kind = HEADERS;
+    let mut flags = if end_stream { END_STREAM } else { 0 };
+    let mut out = Vec::new();
+    while let Some(chunk) = chunks.next() {
+        if chunks.peek().is_none() {
+            flags |= END_HEADERS;
+        }
+        out.extend_from_slice(&encode_frame(kind, flags, stream, chunk));
+        kind = CONTINUATION;
+        flags = 0;
+    }
+    conn.write_all(&out)
+}
+
+// Reads the CONTINUATION frames that complete a header block.
+fn read_header_block(conn: &mut impl Read, first: &Frame) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut block = frame_content(first)?.to_vec();
+    let mut end = first.flags & END_HEADERS != 0;
+    while !end {
+        let next = read_frame(conn)?;
+        if next.kind != CONTINUATION || next.stream != first.stream {
+            return Err("HTTP/2 header block interrupted by another frame".into());
+        }
+        block.extend_from_slice(&next.payload);
+        end = next.flags & END_HEADERS != 0;
+    }
+    Ok(block)
+}
+
+fn send_preface(conn: &mut impl Write) -> std::io::Result<()> {
+    let mut settings = Vec::new();
+    for (id, value) in [
+        (SETTINGS_ENABLE_PUSH, 0),
+        (SETTINGS_INITIAL_WINDOW_SIZE, RECEIVE_WINDOW),
+    ] {
+        settings.extend_from_slice(&id.to_be_bytes());
+        settings.extend_from_slice(&value.to_be_bytes());
+    }
+    let window_increment = RECEIVE_WINDOW - u32::try_from(DEFAULT_WINDOW).unwrap_or_default();
+    let mut start = PREFACE.to_vec();
+    start.extend_from_slice(&encode_frame(SETTINGS, 0, 0, &settings));
+    start.extend_from_slice(&encode_frame(
+        WINDOW_UPDATE,
+        0,
+        0,
+        &window_increment.to_be_bytes(),
+    ));
+    conn.write_all(&start)
+}
+
+#[derive(Default)]
+struct Incoming {
+    status: Option<u16>,
+    headers: Vec<(String, String)>,
+    trailers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Incoming {
+    fn data(&mut self, frame: &Frame) -> Result<(), Box<dyn Error>> {
+        if self.status.is_none() {
+            return Err("HTTP/2 DATA frame before the response headers".into());
+        }
+        let content = frame_content(frame)?;
+        if self.body.len() + content.len() > MAX_BODY {
+            return Err("HTTP/2 response body exceeds 64 MiB".into());
+        }
+        self.body.extend_from_slice(content);
+        Ok(())
+    }
+
+    fn header_block(&mut self, fields: Vec<(String, String)>) -> Result<(), Box<dyn Error>> {
+        if self.status.is_some() {
+            self.trailers = fields;
+            return Ok(());
+        }
+        let status = fields
+            .iter()
+            .find(|(name, _)| name == ":status")
+            .and_then(|(_, value)| value.parse::<u16>().ok())
+            .ok_or("HTTP/2 response without a valid :status")?;
+        // Informational responses are followed by the real one.
+        if !(100..200).contains(&status) {
+            self.status = Some(status);
+            self.headers = fields
+                .into_iter()
+                .filter(|(name, _)| !name.starts_with(':'))
+                .collect();
+        }
+        Ok(())
+    }
+}
+
+// Sends one request as a new stream and reads the response. Also returns the time from
+// the request being sent to the response headers. None means the server closed the
+// connection or refused new streams before it could have processed this one.
+pub fn exchange(
+    conn: &mut (impl Read + Write),
+    session: &mut Session,
+    head: &str,
+    body: Option<&[u8]>,
+) -> Result<Option<(Response, Duration)>, Box<dyn Error>> {
+    let Some(stream) = session.open_stream(conn)? else {
+        return Ok(None);
+    };
+    let block = hpack::encode(&request_fields(head)?);
+    send_headers(conn, stream, &block, body.is_none())?;
+    let sent = Instant::now();
+    let mut upload = Upload {
+        data: body.unwrap_or_default(),
+        done: body.is_none(),
+        stream,
+        stream_window: session.initial_window,
+    };
+    upload.send(conn, session)?;
+
+    let mut ttfb = None;
+    let mut incoming = Incoming::default();
+    // DATA on any stream uses up the connection's receive window.
+    let mut received = 0;
+    loop {
+        let Some(frame) = next_frame(conn)? else {
+            // Closed before answering, like a reused connection the server dropped while idle.
+            if ttfb.is_none() {
+                return Ok(None);
+            }
+            return Err("Connection closed inside the HTTP/2 response".into());
+        };
+        let ours = frame.stream == stream;
+        let mut end_stream = false;
+        match frame.kind {
+            SETTINGS if frame.flags & ACK == 0 => {
+                upload.stream_window += session.apply_settings(&frame.payload);
+                conn.write_all(&encode_frame(SETTINGS, ACK, 0, &[]))?;
+                upload.send(conn, session)?;
+            }
+            PING if frame.flags & ACK == 0 => {
+                conn.write_all(&encode_frame(PING, ACK, 0, &frame.payload))?;
+            }
+            WINDOW_UPDATE => {
+                let increment = i64::from(u32_at(&frame.payload, 0) & 0x7fff_ffff);
+                if frame.stream == 0 {
+                    session.connection_window += increment;
+                } else if ours {
+                    upload.stream_window += increment;
+                }
+                upload.send(conn, session)?;
+            }
+            GOAWAY => {
+                session.closing = true;
+                if u32_at(&frame.payload, 0) & 0x7fff_ffff < stream {
+                    return Ok(None);
+                }
+            }
+            RST_STREAM if ours => {
+                return Err(format!(
+                    "Server reset the HTTP/2 stream ({})",
+                    error_name(&frame.payload, 0)
+                )
+                .into());
+            }
+            PUSH_PROMISE => return Err("Server pushed a stream although push is disabled".into()),
+            HEADERS => {
+                let block = read_header_block(conn, &frame)?;
+                // Every header block must be decoded to keep the HPACK table in sync.
+                let fields = session.decoder.decode(&block)?;
+                if ours {
+                    ttfb.get_or_insert_with(|| sent.elapsed());
+                    end_stream = frame.flags & END_STREAM != 0;
+                    incoming.header_block(fields)?;
+                }
+            }
+            DATA => {
+                received += frame.payload.len();
+                if ours {
+                    incoming.data(&frame)?;
+                    end_stream = frame.flags & END_STREAM != 0;
+                }
+            }
+            _ => {}
+        }
+        if end_stream {
+            break;
+        }
+    }
+    let status = incoming
+        .status
+        .ok_or("HTTP/2 stream ended without a response")?;
+    if received > 0 {
+        let increment = u32::try_from(received).unwrap_or_default();
+        conn.write_all(&encode_frame(WINDOW_UPDATE, 0, 0, &increment.to_be_bytes()))?;
+    }
+
+    Ok(Some((
+        Response {
+            status,
+            headers: incoming.headers,
+            body: incoming.body,
+            trailers: incoming.trailers,
+            keep_alive: !session.closing,
+        },
+        ttfb.unwrap_or_else(|| sent.elapsed()),
+    )))
+}
@@ -1,76 +1,404 @@
-use crate::https;
+use crate::audit;
+use crate::colors::Colorize;
+use crate::expect::Expectations;
+use crate::har::HarLog;
+use crate::https::{
+    self, Credentials, HttpClient, HttpReply, RequestSpec, Routes, Timings, TlsContext, TlsOptions,
+};
+use crate::load::LoadOptions;
+use crate::output::{color_time, print_statistics};
+use crate::parser::{Parser, default_port_for};
+use crate::watch::{ContentChange, ContentWatch, WatchOptions};
+use std::collections::VecDeque;
 use std::error::Error;
+use std::fmt::Write;
+use std::sync::{Mutex, PoisonError};
 use std::thread::sleep;
 use std::time::Duration;
 
-pub fn perform_http_check(
-    url: &str,
-    timeout: u64,
-    count: usize,
-    minimal: bool,
-    headers: &[String],
-) {
-    for i in 0..count {
-        match check_http_status(url, minimal, timeout, headers) {
-            Ok(status) => {
+#[allow(clippy::struct_excessive_bools)]
+pub struct HttpOptions<'a> {
+    pub timeout: u64,
+    pub count: usize,
+    pub minimal: bool,
+    pub method: &'a str,
+    pub headers: &'a [String],
+    pub body: Option<&'a [u8]>,
+    pub compressed: bool,
+    pub follow: Option<usize>,
+    pub expect: &'a Expectations,
+    pub tls: &'a TlsContext,
+    pub routes: &'a Routes,
+    pub verbose: bool,
+    pub auth: Option<&'a Credentials>,
+    pub load: Option<LoadOptions>,
+    pub watch: Option<&'a WatchOptions>,
+    pub audit: bool,
+    pub har: Option<&'a Mutex<HarLog>>,
+    pub tls_scan: Option<&'a TlsOptions>,
+}
+
+pub fn perform_http_check(url: &str, opts: &HttpOptions<'_>) {
+    let mut times: VecDeque<u128> = VecDeque::new();
+    let mut successes = 0usize;
+    let mut client = HttpClient::new(opts.tls.clone(), opts.routes.clone());
+    let mut state = CheckState {
+        watch: opts.watch.map(ContentWatch::new),
+        audit_pending: opts.audit,
+    };
+
+    for i in 0..opts.count {
+        match check_http_status(url, opts, &mut client, &mut state) {
+            Ok((status, total)) => {
+                successes += 1;
+                times.push_back(total.as_micros());
                 println!("{status}");
             }
             Err(e) => {
+                times.push_back(0);
                 println!("{e}");
             }
         }
-        if i < count - 1 {
+        if i < opts.count - 1 {
             sleep(Duration::from_secs(1));
         }
     }
+
+    print_statistics("HTTP", opts.count, successes, &times);
+}
+
+pub fn with_prefix(minimal: bool, message: &str) -> String {
+    if minimal {
+        message.to_string()
+    } else {
+        format!("{} {}", "[MEOWPING]".magenta_str(), message)
+    }
+}
+
+pub fn redact_url(url: &str) -> String {
+    Parser::parse(url).map_or_else(|_| url.to_string(), |parsed| parsed.redacted())
+}
+
+fn same_origin(a: &Parser, b: &Parser) -> bool {
+    let port = |url: &Parser| url.port.or_else(|| default_port_for(&url.scheme));
+    a.scheme == b.scheme && a.host == b.host && port(a) == port(b)
+}
+
+pub fn print_exchange(reply: &HttpReply) {
+    for line in reply.request_head.iter().filter(|line| !line.is_empty()) {
+        println!("> {line}");
+    }
+    println!("< HTTP {}", reply.status);
+    for (name, value) in &reply.headers {
+        println!("< {}", https::redact_header(&format!("{name}: {value}")));
+    }
+}
+
+fn record_har(opts: &HttpOptions<'_>, write: impl FnOnce(&mut HarLog) -> std::io::Result<()>) {
+    if let Some(har) = opts.har
+        && let Err(e) = write(&mut har.lock().unwrap_or_else(PoisonError::into_inner))
+    {
+        println!(
+            "{}",
+            with_prefix(opts.minimal, &format!("Failed to write HAR file: {e}"))
+        );
+    }
+}
+
+// What carries over from one attempt to the next.
+struct CheckState<'a> {
+    watch: Option<ContentWatch<'a>>,
+    // The scorecard is printed once, after the first online response.
+    audit_pending: bool,
+}
+
+struct RedirectChain {
+    first_redirect: Option<String>,
+    final_url: String,
+    reply: HttpReply,
+    redirects: usize,
+    elapsed: Duration,
+}
+
+fn fetch_following(
+    url: &str,
+    opts: &HttpOptions<'_>,
+    client: &mut HttpClient,
+) -> Result<RedirectChain, Box<dyn Error>> {
+    let mut visited = vec![url.to_string()];
+    let mut first_redirect = None;
+    let mut elapsed = Duration::ZERO;
+    // What is left of -H and config headers once a redirect leaves the original origin.
+    let public_headers: Vec<String> = opts
+        .headers
+        .iter()
+        .filter(|line| {
+            !line
+                .split_once(':')
+                .is_some_and(|(name, _)| https::is_secret_header(name))
+        })
+        .cloned()
+        .collect();
+    let mut spec = RequestSpec {
+        method: opts.method,
+        headers: opts.headers,
+        body: opts.body,
+        compressed: opts.compressed,
+        auth: opts.auth,
+    };
+
+    loop {
+        let current = visited.last().ok_or("Empty redirect chain")?.clone();
+        let reply = match client.request(&current, opts.timeout, &spec) {
+            Ok(reply) => reply,
+            Err(e) => {
+                record_har(opts, |har| {
+                    har.record_error(&current, &spec, &e.to_string())
+                });
+                return Err(e);
+            }
+        };
+        record_har(opts, |har| har.record(&current, &spec, &reply));
+        elapsed += reply.timings.total;
+        if opts.verbose {
+            print_exchange(&reply);
+        }
+        if first_redirect.is_none()
+            && let Some(location) = reply.redirect_location()
+        {
+            first_redirect = Some(Parser::parse(&current)?.join(location));
+        }
+
+        let next = match (opts.follow, reply.redirect_location()) {
+            (Some(limit), Some(location)) => {
+                let current_url = Parser::parse(&current)?;
+                let next = current_url.join(location);
+                // Never forward credentials, cookies or other secret headers to another origin.
+                if !same_origin(&current_url, &Parser::parse(&next)?) {
+                    spec.auth = None;
+                    spec.headers = &public_headers;
+                }
+                println!(
+                    "{}",
+                    with_prefix(
+                        opts.minimal,
+                        &format!(
+                            "{} redirected ({}) to {} {}",
+                            redact_url(&current),
+                            reply.status,
+                            redact_url(&next),
+                            format_timings(&reply.timings)
+                        )
+                    )
+                );
+                if visited.contains(&next) {
+                    return Err(
+                        format!("Redirect loop detected: {next} was already visited").into(),
+                    );
+                }
+                if visited.len() > limit {
+                    return Err(format!("Too many redirects (limit {limit})").into());
+                }
+                let rewrites_to_get = match reply.status {
+                    303 => spec.method != "HEAD",
+                    301 | 302 => spec.method == "POST",
+                    _ => false,
+                };
+                if rewrites_to_get {
+                    spec.method = "GET";
+                    spec.body = None;
+                }
+                next
+            }
+            _ => {
+                return Ok(RedirectChain {
+                    first_redirect,
+                    final_url: current,
+                    reply,
+                    redirects: visited.len() - 1,
+                    elapsed,
+                });
+            }
+        };
+        visited.push(next);
+    }
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn format_size(bytes: usize) -> String {
+    let value = f64::from(u32::try_from(bytes).unwrap_or(u32::MAX));
+    match bytes {
+        0..1024 => format!("{bytes}B"),
+        1024..1_048_576 => format!("{:.1}KiB", value / 1024.0),
+        _ => format!("{:.1}MiB", value / 1_048_576.0),
+    }
+}
+
+fn format_encoding(reply: &HttpReply) -> String {
+    reply
+        .encoding
+        .as_ref()
+        .map_or_else(String::new, |encoding| {
+            if encoding.decoded {
+                format!(
+                    " body={} ({} {})",
+                    format_size(reply.body.len()),
+                    encoding.name,
+                    format_size(encoding.encoded_len)
+                )
+            } else {
+                format!(
+                    " body={} ({}, not decoded)",
+                    format_size(encoding.encoded_len),
+                    encoding.name
+                )
+            }
+        })
+}
+
+pub fn format_timings(timings: &Timings) -> String {
+    if timings.reused {
+        return format!(
+            "time={} (reused connection ttfb={:.2}ms)",
+            color_time(ms(timings.total)),
+            ms(timings.ttfb),
+        );
+    }
+    let proxy = timings
+        .proxy
+        .map_or_else(String::new, |proxy| format!(" proxy={:.2}ms", ms(proxy)));
+    let tls = timings
+        .tls
+        .map_or_else(String::new, |tls| format!(" tls={:.2}ms", ms(tls)));
+    format!(
+        "time={} (dns={:.2}ms connect={:.2}ms{proxy}{tls} ttfb={:.2}ms)",
+        color_time(ms(timings.total)),
+        ms(timings.dns),
+        ms(timings.connect),
+        ms(timings.ttfb),
+    )
 }
 
 fn check_http_status(
     url: &str,
-    minimal: bool,
-    timeout: u64,
-    headers: &[String],
-) -> Result<String, Box<dyn Error>> {
-    match https::get_status(url, timeout, headers) {
-        Ok(status) => {
-            let (status_text, is_online) = match status {
+    opts: &HttpOptions<'_>,
+    client: &mut HttpClient,
+    state: &mut CheckState<'_>,
+) -> Result<(String, Duration), Box<dyn Error>> {
+    let minimal = opts.minimal;
+    match fetch_following(url, opts, client) {
+        Ok(chain) => {
+            let status = chain.reply.status;
+            let (status_text, mut is_online) = match status {
+                _ if opts.expect.has_status() => ("online", true),
                 200..=399 => ("online", true),
                 400..=499 => ("online (client error)", true),
                 500..=599 => ("offline (server error)", false),
                 _ => ("unknown status", true),
             };
 
-            let message = format!("{url} is {status_text}. HTTP status: {status}");
-            let formatted = if minimal {
-                message
+            let failures = opts.expect.failures(&chain.reply, chain.elapsed);
+            let verdict = if failures.is_empty() {
+                format!("is {status_text}")
             } else {
-                format!("{} {}", "[MEOWPING]".magenta_str(), message)
+                is_online = false;
+                format!("failed the check ({})", failures.join("; "))
             };
+            let protocol = chain
+                .reply
+                .protocol
+                .map_or_else(String::new, |protocol| format!(" protocol={protocol}"));
+            let mut message = format!(
+                "{} {verdict}. HTTP status: {status}{protocol} {}{}",
+                redact_url(&chain.final_url),
+                format_timings(&chain.reply.timings),
+                format_encoding(&chain.reply)
+            );
+            if let Some(via) = &chain.reply.via {
+                let _ = write!(message, " via {via}");
+            }
+            if opts.verbose
+                && let Some(peer) = &chain.reply.peer
+            {
+                let _ = write!(message, " {}", peer.describe());
+            }
+            if chain.redirects > 0 {
+                let _ = write!(
+                    message,
+                    " after {} redirect(s), chain total={}",
+                    chain.redirects,
+                    color_time(ms(chain.elapsed))
+                );
+            }
+            let mut diff = Vec::new();
+            if is_online && let Some(watch) = &mut state.watch {
+                match watch.observe(&chain.reply.body) {
+                    ContentChange::First(hash) | ContentChange::Same(hash) => {
+                        let _ = write!(message, " content={hash}");
+                    }
+                    ContentChange::Changed {
+                        from,
+                        to,
+                        diff: changed,
+                    } => {
+                        let _ = write!(
+                            message,
+                            " {}",
+                            format!("content changed {from} -> {to}").yellow()
+                        );
+                        diff = changed;
+                    }
+                }
+            }
+            let mut formatted = with_prefix(minimal, &message);
+            for line in diff {
+                let _ = write!(formatted, "\n    {line}");
+            }
+            if is_online && state.audit_pending {
+                state.audit_pending = false;
+                let card = audit::scorecard(
+                    url,
+                    chain.first_redirect.as_deref(),
+                    &chain.final_url,
+                    &chain.reply,
+                    minimal,
+                );
+                let _ = write!(formatted, "\n{card}");
+            }
 
             if is_online {
-                Ok(formatted)
+                Ok((formatted, chain.elapsed))
             } else {
                 Err(formatted.into())
             }
         }
         Err(e) => {
             let error_str = e.to_string();
-            let simplified_error = match error_str.as_str() {
-                s if s.contains("address information") || s.contains("nodename nor servname") => {
-                    "Failed to resolve host"
-                }
-                s if s.contains("timed out") || s.contains("timeout") => "Connection timed out",
-                s if s.contains("refused") => "Connection refused",
-                _ => &error_str,
-            };
+            Err(with_prefix(minimal, simplify_error(&error_str)).into())
+        }
+    }
+}
 
-            let error_msg = if minimal {
-                simplified_error.to_string()
-            } else {
-                format!("{} {}", "[MEOWPING]".magenta_str(), simplified_error)
-            };
-            Err(error_msg.into())
+pub fn simplify_error(error: &str) -> &str {
+    match error {
+        s if s.contains("address information") || s.contains("nodename nor servname") => {
+            "Failed to resolve host"
+        }
+        s if s.contains("timed out") || s.contains("timeout") => "Connection timed out",
+        s if s.contains("refused") => "Connection refused",
+        s if s.contains("alert protocol version") || s.contains("unsupported protocol") => {
+            "TLS version rejected: the server supports none of the allowed versions (--tls-min/--tls-max)"
+        }
+        s if s.contains("certificate required") || s.contains("handshake failure") => {
+            "TLS handshake rejected: the server may require a client certificate (use --cert)"
+        }
+        s if s.contains("certificate verify failed") => {
+            "TLS certificate verification failed (use --cacert for a private CA, or --insecure)"
         }
+        _ => error,
     }
 }
 
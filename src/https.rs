@@ -1,9 +1,21 @@
-use crate::parser::Parser;
-use native_tls::TlsConnector;
-use std::io::{Read, Write};
+use crate::digest;
+use crate::encoding;
+use crate::http1;
+use crate::http2;
+use crate::inflate;
+use crate::parser::{Parser, host_without_zone, parse_scoped_ip, scoped_socket_addr};
+use crate::proxy::{self, Proxy, ProxyKind};
+use crate::x509;
+use native_tls::{Certificate, Identity, Protocol, TlsConnector, TlsStream};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
 use std::net::TcpStream;
 use std::net::ToSocketAddrs;
-use std::time::Duration;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 const DEFAULT_HEADERS: &[&str] = &[
     "User-Agent: Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/115.0.0.0 Safari/537.36",
@@ -11,94 +23,848 @@ const DEFAULT_HEADERS: &[&str] = &[
     "Accept-Language: en-US,en;q=0.9",
 ];
 
-fn build_request(host: &str, path: &str, extra_headers: &[String]) -> String {
-    let mut req = format!("GET {path} HTTP/1.1\r\nHost: {host}\r\n");
-    let headers: &[_] = if extra_headers.is_empty() {
+pub struct RequestSpec<'a> {
+    pub method: &'a str,
+    pub headers: &'a [String],
+    pub body: Option<&'a [u8]>,
+    pub compressed: bool,
+    pub auth: Option<&'a Credentials>,
+}
+
+impl RequestSpec<'_> {
+    pub const GET: RequestSpec<'static> = RequestSpec {
+        method: "GET",
+        headers: &[],
+        body: None,
+        compressed: false,
+        auth: None,
+    };
+}
+
+pub enum Credentials {
+    Basic(String, String),
+    Bearer(String),
+    // Read again for every request so rotated tokens are picked up.
+    TokenFile(PathBuf),
+}
+
+impl Credentials {
+    pub fn authorization(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(match self {
+            Self::Basic(user, password) => format!(
+                "Basic {}",
+                encoding::base64_encode(format!("{user}:{password}").as_bytes())
+            ),
+            Self::Bearer(token) => format!("Bearer {token}"),
+            Self::TokenFile(path) => {
+                let data = read_file(path, "token file")?;
+                let token = String::from_utf8_lossy(&data).trim().to_string();
+                if token.is_empty() {
+                    return Err(format!("Token file '{}' is empty", path.display()).into());
+                }
+                format!("Bearer {token}")
+            }
+        })
+    }
+}
+
+pub fn is_secret_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    matches!(
+        name.as_str(),
+        "authorization" | "proxy-authorization" | "cookie" | "set-cookie"
+    ) || ["token", "secret", "api-key", "apikey", "password"]
+        .iter()
+        .any(|word| name.contains(word))
+}
+
+// Replaces credentials in a header line, keeping the auth scheme (e.g. "Bearer") visible.
+pub fn redact_header(line: &str) -> String {
+    let Some((name, value)) = line.split_once(':') else {
+        return line.to_string();
+    };
+    if !is_secret_header(name) {
+        return line.to_string();
+    }
+    let value = value.trim();
+    let is_auth = name.trim().eq_ignore_ascii_case("authorization")
+        || name.trim().eq_ignore_ascii_case("proxy-authorization");
+    match value.split_once(' ') {
+        Some((scheme, _)) if is_auth => format!("{name}: {scheme} [redacted]"),
+        _ => format!("{name}: [redacted]"),
+    }
+}
+
+fn header_name(header: &str) -> &str {
+    header
+        .split_once(':')
+        .map_or(header, |(name, _)| name)
+        .trim()
+}
+
+pub fn merge_headers(base: &[String], overrides: &[String]) -> Vec<String> {
+    let mut merged: Vec<String> = if base.is_empty() {
+        DEFAULT_HEADERS
+            .iter()
+            .map(std::string::ToString::to_string)
+            .collect()
+    } else {
+        base.to_vec()
+    };
+    for header in overrides {
+        let name = header_name(header);
+        merged.retain(|existing| !header_name(existing).eq_ignore_ascii_case(name));
+        let removes = header
+            .split_once(':')
+            .is_some_and(|(_, value)| value.trim().is_empty());
+        if !removes {
+            merged.push(header.clone());
+        }
+    }
+    merged
+}
+
+fn build_head(
+    url: &Parser,
+    target: &str,
+    spec: &RequestSpec<'_>,
+    keep_alive: bool,
+    proxy_auth: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut req = format!(
+        "{} {target} HTTP/1.1\r\nHost: {}\r\n",
+        spec.method,
+        url.host_header()
+    );
+    if let Some(auth) = proxy_auth {
+        let _ = write!(req, "Proxy-Authorization: {auth}\r\n");
+    }
+    let headers: &[_] = if spec.headers.is_empty() {
         &DEFAULT_HEADERS
             .iter()
             .map(std::string::ToString::to_string)
             .collect::<Vec<_>>()
     } else {
-        extra_headers
+        spec.headers
+    };
+    let has_header = |name: &str| {
+        headers
+            .iter()
+            .any(|h| header_name(h).eq_ignore_ascii_case(name))
     };
     for h in headers {
         req.push_str(h);
         req.push_str("\r\n");
     }
-    req.push_str("Connection: close\r\n\r\n");
-    req
+    if !has_header("Authorization") {
+        if let Some(auth) = spec.auth {
+            let _ = write!(req, "Authorization: {}\r\n", auth.authorization()?);
+        } else if url.has_credentials() {
+            let basic = Credentials::Basic(
+                url.username.clone(),
+                url.password.clone().unwrap_or_default(),
+            );
+            let _ = write!(req, "Authorization: {}\r\n", basic.authorization()?);
+        }
+    }
+    if spec.compressed && !has_header("Accept-Encoding") {
+        req.push_str("Accept-Encoding: gzip, deflate\r\n");
+    }
+    if let Some(body) = spec.body {
+        if !has_header("Content-Type") {
+            req.push_str("Content-Type: application/x-www-form-urlencoded\r\n");
+        }
+        if !has_header("Content-Length") {
+            let _ = write!(req, "Content-Length: {}\r\n", body.len());
+        }
+    }
+    if !keep_alive {
+        req.push_str("Connection: close\r\n");
+    }
+    req.push_str("\r\n");
+    Ok(req)
+}
+
+pub struct HttpReply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub trailers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub encoding: Option<ContentEncoding>,
+    pub timings: Timings,
+    pub peer: Option<PeerCertificate>,
+    // Negotiated through ALPN, so only known for TLS connections.
+    pub protocol: Option<HttpVersion>,
+    pub via: Option<String>,
+    // The request head as sent, with credentials redacted.
+    pub request_head: Vec<String>,
+}
+
+pub struct ContentEncoding {
+    pub name: String,
+    pub encoded_len: usize,
+    pub decoded: bool,
+}
+
+fn decode_body(
+    headers: &[(String, String)],
+    body: Vec<u8>,
+) -> Result<(Vec<u8>, Option<ContentEncoding>), Box<dyn std::error::Error>> {
+    let Some((_, value)) = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Encoding"))
+    else {
+        return Ok((body, None));
+    };
+    let codings: Vec<String> = value
+        .split(',')
+        .map(|c| c.trim().to_ascii_lowercase())
+        .filter(|c| !c.is_empty() && c != "identity")
+        .collect();
+    if codings.is_empty() || body.is_empty() {
+        return Ok((body, None));
+    }
+
+    let encoded_len = body.len();
+    let supported = codings
+        .iter()
+        .all(|c| matches!(c.as_str(), "gzip" | "x-gzip" | "deflate"));
+    let mut decoded = body;
+    if supported {
+        for coding in codings.iter().rev() {
+            decoded = match coding.as_str() {
+                "deflate" => inflate::zlib_or_raw(&decoded),
+                _ => inflate::gunzip(&decoded),
+            }
+            .map_err(|e| format!("Failed to decode {coding} body: {e}"))?;
+        }
+    }
+    Ok((
+        decoded,
+        Some(ContentEncoding {
+            name: codings.join(", "),
+            encoded_len,
+            decoded: supported,
+        }),
+    ))
 }
 
-fn parse_http_status(response: &[u8]) -> Result<u16, Box<dyn std::error::Error>> {
-    let status_line = response
-        .split(|ch| *ch == b'\n')
-        .next()
-        .ok_or("No response status line")?;
-    let status_line = status_line.strip_suffix(b"\r").unwrap_or(status_line);
-    let status_line = str::from_utf8(status_line).map_err(|_| "Failed to parse status code")?;
+impl HttpReply {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .chain(&self.trailers)
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn redirect_location(&self) -> Option<&str> {
+        match self.status {
+            301 | 302 | 303 | 307 | 308 => self.header("Location"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Timings {
+    pub dns: Duration,
+    pub connect: Duration,
+    pub proxy: Option<Duration>,
+    pub tls: Option<Duration>,
+    pub ttfb: Duration,
+    pub total: Duration,
+    pub reused: bool,
+}
 
-    let status_code = status_line
-        .split_whitespace()
-        .nth(1)
-        .ok_or("Failed to parse status code")?
-        .parse::<u16>()?;
+#[derive(Clone, Copy)]
+pub struct PeerCertificate {
+    pub cert_sha256: [u8; 32],
+    pub spki_sha256: Option<[u8; 32]>,
+    // Unix seconds.
+    pub not_after: Option<i64>,
+}
+
+impl PeerCertificate {
+    fn from_der(der: &[u8]) -> Self {
+        Self {
+            cert_sha256: digest::sha256(der),
+            spki_sha256: x509::subject_public_key_info(der).map(digest::sha256),
+            not_after: x509::not_after(der),
+        }
+    }
 
-    Ok(status_code)
+    fn matches(&self, pins: &[[u8; 32]]) -> bool {
+        pins.iter()
+            .any(|pin| *pin == self.cert_sha256 || Some(*pin) == self.spki_sha256)
+    }
+
+    pub fn describe(&self) -> String {
+        let mut text = format!("cert sha256/{}", encoding::base64_encode(&self.cert_sha256));
+        if let Some(spki) = self.spki_sha256 {
+            let _ = write!(text, " spki sha256/{}", encoding::base64_encode(&spki));
+        }
+        text
+    }
 }
 
-fn parse_http_body(response: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
-    let body_idx = response
-        .windows(4)
-        .position(|double_newline| double_newline == b"\r\n\r\n")
-        .ok_or("No response body")?;
-    Ok(String::from_utf8_lossy(&response[body_idx + 4..]).into_owned())
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpVersion {
+    Http1,
+    Http2,
+}
+
+impl std::fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Http1 => "HTTP/1.1",
+            Self::Http2 => "HTTP/2",
+        })
+    }
+}
+
+enum Connection {
+    Plain(BufReader<TcpStream>),
+    Tls(
+        Box<BufReader<TlsStream<TcpStream>>>,
+        Option<PeerCertificate>,
+        // Present when the connection negotiated h2.
+        Option<http2::Session>,
+    ),
+    #[cfg(unix)]
+    Unix(BufReader<UnixStream>),
+}
+
+impl Connection {
+    const fn peer(&self) -> Option<PeerCertificate> {
+        match self {
+            Self::Plain(_) => None,
+            Self::Tls(_, peer, _) => *peer,
+            #[cfg(unix)]
+            Self::Unix(_) => None,
+        }
+    }
+
+    const fn protocol(&self) -> Option<HttpVersion> {
+        match self {
+            Self::Plain(_) => None,
+            Self::Tls(_, _, session) => Some(if session.is_some() {
+                HttpVersion::Http2
+            } else {
+                HttpVersion::Http1
+            }),
+            #[cfg(unix)]
+            Self::Unix(_) => None,
+        }
+    }
+
+    fn reader(&mut self) -> &mut dyn BufRead {
+        match self {
+            Self::Plain(reader) => reader,
+            Self::Tls(reader, _, _) => reader.as_mut(),
+            #[cfg(unix)]
+            Self::Unix(reader) => reader,
+        }
+    }
+
+    fn tcp(&self) -> Option<&TcpStream> {
+        match self {
+            Self::Plain(reader) => Some(reader.get_ref()),
+            Self::Tls(reader, _, _) => Some(reader.get_ref().get_ref()),
+            #[cfg(unix)]
+            Self::Unix(_) => None,
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+        match self {
+            Self::Plain(reader) => reader.get_ref().set_read_timeout(Some(timeout)),
+            Self::Tls(reader, _, _) => reader.get_ref().get_ref().set_read_timeout(Some(timeout)),
+            #[cfg(unix)]
+            Self::Unix(reader) => reader.get_ref().set_read_timeout(Some(timeout)),
+        }
+    }
+
+    fn peek_nonblocking(&self) -> std::io::Result<usize> {
+        let tcp = match self {
+            Self::Plain(reader) => reader.get_ref(),
+            Self::Tls(reader, _, _) => reader.get_ref().get_ref(),
+            #[cfg(unix)]
+            Self::Unix(reader) => {
+                use std::os::fd::AsRawFd;
+                let mut byte = 0u8;
+                let peeked = unsafe {
+                    libc::recv(
+                        reader.get_ref().as_raw_fd(),
+                        (&raw mut byte).cast(),
+                        1,
+                        libc::MSG_PEEK | libc::MSG_DONTWAIT,
+                    )
+                };
+                return usize::try_from(peeked).map_err(|_| std::io::Error::last_os_error());
+            }
+        };
+        tcp.set_nonblocking(true)?;
+        let peeked = tcp.peek(&mut [0]);
+        tcp.set_nonblocking(false)?;
+        peeked
+    }
+
+    // Whether a connection back from the pool can carry another request. EOF means the
+    // server closed it. Bytes that arrived while it sat idle mean an HTTP/1.1 server is done
+    // with it; on HTTP/2 they are frames (SETTINGS, PING, GOAWAY) the next exchange reads.
+    fn reusable(&self) -> bool {
+        match self.peek_nonblocking() {
+            Err(e) => e.kind() == std::io::ErrorKind::WouldBlock,
+            Ok(0) => false,
+            Ok(_) => self.protocol() == Some(HttpVersion::Http2),
+        }
+    }
+
+    // Sends the request and reads the response. None means the connection closed before any
+    // of the response arrived, so the server cannot have answered: the write failed, or EOF
+    // came first. A read timeout is an error, never None.
+    fn exchange(
+        &mut self,
+        head: &str,
+        request: &[u8],
+        spec: &RequestSpec<'_>,
+        timeout: u64,
+        timings: &mut Timings,
+    ) -> Result<Option<http1::Response>, Box<dyn std::error::Error>> {
+        if let Self::Tls(_, _, session) = self
+            && let Some(mut h2) = session.take()
+        {
+            let result = http2::exchange(self, &mut h2, head, spec.body);
+            if let Self::Tls(_, _, session) = self {
+                *session = Some(h2);
+            }
+            let exchanged = result.map_err(|e| match e.downcast::<std::io::Error>() {
+                Ok(e) => read_error(*e, timeout),
+                Err(e) => e,
+            })?;
+            return Ok(exchanged.map(|(response, ttfb)| {
+                timings.ttfb = ttfb;
+                response
+            }));
+        }
+
+        if self.write_all(request).is_err() {
+            return Ok(None);
+        }
+        let sent = Instant::now();
+        match self.reader().fill_buf() {
+            Ok([]) => return Ok(None),
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => return Ok(None),
+            Err(e) => return Err(read_error(e, timeout)),
+        }
+        timings.ttfb = sent.elapsed();
+        let response = http1::read_response(&mut self.reader(), spec.method == "HEAD")?;
+        Ok(Some(response))
+    }
+
+    fn send(&mut self, request: &[u8], timings: &mut Timings) -> std::io::Result<bool> {
+        self.write_all(request)?;
+        let sent = Instant::now();
+        let has_response = !self.reader().fill_buf()?.is_empty();
+        timings.ttfb = sent.elapsed();
+        Ok(has_response)
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader().read(buf)
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(reader) => reader.get_mut().write(buf),
+            Self::Tls(reader, _, _) => reader.get_mut().write(buf),
+            #[cfg(unix)]
+            Self::Unix(reader) => reader.get_mut().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// A connection handed over after "101 Switching Protocols".
+pub struct Upgraded(Connection);
+
+impl Read for Upgraded {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for Upgraded {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 fn connect_tcp(
     host: &str,
     port: u16,
     timeout: u64,
+    timings: &mut Timings,
 ) -> Result<TcpStream, Box<dyn std::error::Error>> {
-    let addr = (host, port)
-        .to_socket_addrs()?
-        .next()
-        .ok_or("Invalid address")?;
-    Ok(TcpStream::connect_timeout(
-        &addr,
-        Duration::from_millis(timeout),
-    )?)
+    let dns_start = Instant::now();
+    let addr = match parse_scoped_ip(host) {
+        Some((ip, scope_id)) => scoped_socket_addr(ip, port, scope_id),
+        None => (host, port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or("Invalid address")?,
+    };
+    timings.dns = dns_start.elapsed();
+
+    let connect_start = Instant::now();
+    let stream = TcpStream::connect_timeout(&addr, Duration::from_millis(timeout))?;
+    timings.connect = connect_start.elapsed();
+    Ok(stream)
+}
+
+#[cfg(unix)]
+fn connect_unix(
+    path: &Path,
+    timeout: u64,
+    timings: &mut Timings,
+) -> Result<Connection, Box<dyn std::error::Error>> {
+    let connect_start = Instant::now();
+    let stream = UnixStream::connect(path)
+        .map_err(|e| format!("Failed to connect to Unix socket '{}': {e}", path.display()))?;
+    timings.connect = connect_start.elapsed();
+    // There is no connect timeout for Unix sockets, so at least never wait forever for a reply.
+    stream.set_read_timeout(Some(Duration::from_millis(timeout)))?;
+    Ok(Connection::Unix(BufReader::new(stream)))
 }
 
-fn read_response(mut stream: impl Read) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let mut response = Vec::new();
-    stream.read_to_end(&mut response)?;
-    Ok(response)
+#[cfg(not(unix))]
+fn connect_unix(
+    _path: &Path,
+    _timeout: u64,
+    _timings: &mut Timings,
+) -> Result<Connection, Box<dyn std::error::Error>> {
+    Err("Unix domain sockets are not supported on this platform".into())
+}
+
+fn open_tcp(
+    target: (&str, u16),
+    tunnel: Option<&Proxy>,
+    timeout: u64,
+    timings: &mut Timings,
+) -> Result<TcpStream, Box<dyn std::error::Error>> {
+    match tunnel {
+        Some(proxy) => {
+            let stream = connect_tcp(&proxy.host, proxy.port, timeout, timings)?;
+            let tunnel_start = Instant::now();
+            let stream = proxy.tunnel(stream, target.0, target.1, timeout)?;
+            timings.proxy = Some(tunnel_start.elapsed());
+            Ok(stream)
+        }
+        None => connect_tcp(target.0, target.1, timeout, timings),
+    }
 }
 
-fn fetch_response(
+fn open_connection(
     host: &str,
-    port: u16,
-    path: &str,
+    target: (&str, u16),
+    tunnel: Option<&Proxy>,
     timeout: u64,
-    headers: &[String],
-    tls: bool,
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let stream = connect_tcp(host, port, timeout)?;
-    let request = build_request(host, path, headers);
-    if tls {
-        let connector = TlsConnector::new()?;
-        let mut ssl_stream = connector.connect(host, stream)?;
-        ssl_stream.write_all(request.as_bytes())?;
-        read_response(&mut ssl_stream)
-    } else {
-        let mut stream = stream;
-        stream.write_all(request.as_bytes())?;
-        read_response(&mut stream)
+    tls: Option<&TlsContext>,
+    timings: &mut Timings,
+) -> Result<Connection, Box<dyn std::error::Error>> {
+    let stream = open_tcp(target, tunnel, timeout, timings)?;
+    // Covers the TLS handshake too: a server that ignores a ClientHello it doesn't like must
+    // not stall it forever, and a silent server must not stall the response either.
+    stream.set_read_timeout(Some(Duration::from_millis(timeout)))?;
+    let Some(tls) = tls else {
+        return Ok(Connection::Plain(BufReader::new(stream)));
+    };
+    let handshake_start = Instant::now();
+    let ssl_stream = tls.connector.connect(host_without_zone(host), stream)?;
+    timings.tls = Some(handshake_start.elapsed());
+    let session =
+        (ssl_stream.negotiated_alpn()?.as_deref() == Some(b"h2")).then(http2::Session::new);
+    if session.is_some() {
+        // Small control frames (WINDOW_UPDATE, SETTINGS ACK) must not hold back the next request.
+        ssl_stream.get_ref().set_nodelay(true)?;
+    }
+
+    let peer = ssl_stream
+        .peer_certificate()?
+        .map(|cert| cert.to_der())
+        .transpose()?
+        .map(|der| PeerCertificate::from_der(&der));
+    if !tls.pins.is_empty() {
+        match &peer {
+            Some(peer) if peer.matches(&tls.pins) => {}
+            Some(peer) => {
+                return Err(format!("Certificate pin mismatch: served {}", peer.describe()).into());
+            }
+            None => return Err("Certificate pin mismatch: server sent no certificate".into()),
+        }
     }
+    Ok(Connection::Tls(
+        Box::new(BufReader::new(ssl_stream)),
+        peer,
+        session,
+    ))
 }
 
-fn is_https(url: &str, host: &str) -> Result<bool, Box<dyn std::error::Error>> {
-    let https = url.starts_with("https://");
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsVersion {
+    V1_0,
+    V1_1,
+    V1_2,
+    V1_3,
+}
+
+impl TlsVersion {
+    pub const ALL: [Self; 4] = [Self::V1_0, Self::V1_1, Self::V1_2, Self::V1_3];
+
+    // Accepts "1.2", "tls1.2" or "TLSv1.2".
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let lower = input.trim().to_ascii_lowercase();
+        let number = lower
+            .strip_prefix("tlsv")
+            .or_else(|| lower.strip_prefix("tls"))
+            .unwrap_or(&lower);
+        match number {
+            "1.0" | "1" => Ok(Self::V1_0),
+            "1.1" => Ok(Self::V1_1),
+            "1.2" => Ok(Self::V1_2),
+            "1.3" => Ok(Self::V1_3),
+            _ => Err(format!(
+                "Invalid TLS version '{input}': expected 1.0, 1.1, 1.2 or 1.3"
+            )),
+        }
+    }
+
+    const fn protocol(self) -> Protocol {
+        match self {
+            Self::V1_0 => Protocol::Tlsv10,
+            Self::V1_1 => Protocol::Tlsv11,
+            Self::V1_2 => Protocol::Tlsv12,
+            Self::V1_3 => Protocol::Tlsv13,
+        }
+    }
+}
+
+impl std::fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::V1_0 => "TLS 1.0",
+            Self::V1_1 => "TLS 1.1",
+            Self::V1_2 => "TLS 1.2",
+            Self::V1_3 => "TLS 1.3",
+        })
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct TlsOptions {
+    pub cacert: Option<PathBuf>,
+    pub cert: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+    pub cert_password: Option<String>,
+    // Some(false) comes from --no-insecure and overrides `insecure = true` in the config.
+    pub insecure: Option<bool>,
+    pub pins: Vec<[u8; 32]>,
+    pub min_version: Option<TlsVersion>,
+    pub max_version: Option<TlsVersion>,
+}
+
+impl TlsOptions {
+    pub fn or(self, base: &Self) -> Self {
+        Self {
+            cacert: self.cacert.or_else(|| base.cacert.clone()),
+            cert: self.cert.or_else(|| base.cert.clone()),
+            key: self.key.or_else(|| base.key.clone()),
+            cert_password: self.cert_password.or_else(|| base.cert_password.clone()),
+            insecure: self.insecure.or(base.insecure),
+            pins: if self.pins.is_empty() {
+                base.pins.clone()
+            } else {
+                self.pins
+            },
+            min_version: self.min_version.or(base.min_version),
+            max_version: self.max_version.or(base.max_version),
+        }
+    }
+
+    pub const fn skips_verification(&self) -> bool {
+        matches!(self.insecure, Some(true))
+    }
+
+    pub const fn is_set(&self) -> bool {
+        self.cacert.is_some()
+            || self.cert.is_some()
+            || self.key.is_some()
+            || self.cert_password.is_some()
+            || self.insecure.is_some()
+            || !self.pins.is_empty()
+            || self.min_version.is_some()
+            || self.max_version.is_some()
+    }
+}
+
+// Accepts "<base64>", "sha256/<base64>" (HPKP style) or "sha256//<base64>" (curl style).
+pub fn parse_pin(pin: &str) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let value = pin.trim();
+    let value = value
+        .strip_prefix("sha256//")
+        .or_else(|| value.strip_prefix("sha256/"))
+        .unwrap_or(value);
+    encoding::base64_decode(value)
+        .ok()
+        .and_then(|digest| <[u8; 32]>::try_from(digest).ok())
+        .ok_or_else(|| {
+            format!("Invalid pin '{pin}': expected a base64-encoded SHA-256 digest").into()
+        })
+}
+
+fn read_file(path: &Path, what: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    fs::read(path).map_err(|e| format!("Failed to read {what} '{}': {e}", path.display()).into())
+}
+
+fn pem_blocks(data: &[u8], label: &str) -> Vec<Vec<u8>> {
+    let text = String::from_utf8_lossy(data);
+    let begin = format!("-----BEGIN {label}-----");
+    let end = format!("-----END {label}-----");
+    let mut blocks = Vec::new();
+    let mut rest = text.as_ref();
+    while let Some(start) = rest.find(&begin) {
+        let Some(stop) = rest[start..].find(&end) else {
+            break;
+        };
+        let block_end = start + stop + end.len();
+        blocks.push(format!("{}\n", &rest[start..block_end]).into_bytes());
+        rest = &rest[block_end..];
+    }
+    blocks
+}
+
+fn load_identity(tls: &TlsOptions, cert: &Path) -> Result<Identity, Box<dyn std::error::Error>> {
+    let data = read_file(cert, "client certificate")?;
+    let certs = pem_blocks(&data, "CERTIFICATE");
+    if certs.is_empty() {
+        if tls.key.is_some() {
+            return Err(
+                "--key is only used with a PEM --cert; PKCS#12 files carry their own key".into(),
+            );
+        }
+        let password = tls.cert_password.as_deref().unwrap_or_default();
+        return Identity::from_pkcs12(&data, password).map_err(|e| {
+            format!("Failed to load PKCS#12 identity '{}': {e}", cert.display()).into()
+        });
+    }
+
+    let key_data = match &tls.key {
+        Some(key) => read_file(key, "client key")?,
+        None => data,
+    };
+    let Some(key) = pem_blocks(&key_data, "PRIVATE KEY").into_iter().next() else {
+        let legacy = ["RSA PRIVATE KEY", "EC PRIVATE KEY", "ENCRYPTED PRIVATE KEY"]
+            .iter()
+            .any(|label| !pem_blocks(&key_data, label).is_empty());
+        return Err(if legacy {
+            "Client key must be an unencrypted PKCS#8 PEM (BEGIN PRIVATE KEY); convert it with: openssl pkcs8 -topk8 -nocrypt -in key.pem -out key.p8.pem".into()
+        } else {
+            "No private key found: pass --key or put the key in the --cert PEM file".into()
+        });
+    };
+    Identity::from_pkcs8(&certs.concat(), &key)
+        .map_err(|e| format!("Failed to load client certificate/key: {e}").into())
+}
+
+#[derive(Clone)]
+pub struct TlsContext {
+    // Offers h2 and http/1.1 through ALPN.
+    pub connector: TlsConnector,
+    // Offers only http/1.1, for requests that must stay on HTTP/1.1 such as upgrades.
+    pub http1_connector: TlsConnector,
+    pub pins: Vec<[u8; 32]>,
+}
+
+impl TlsContext {
+    fn http1_only(&self) -> Self {
+        Self {
+            connector: self.http1_connector.clone(),
+            ..self.clone()
+        }
+    }
+}
+
+pub fn build_tls_context(tls: &TlsOptions) -> Result<TlsContext, Box<dyn std::error::Error>> {
+    let mut builder = TlsConnector::builder();
+    if let Some(path) = &tls.cacert {
+        let data = read_file(path, "CA bundle")?;
+        let certs = pem_blocks(&data, "CERTIFICATE");
+        if certs.is_empty() {
+            builder.add_root_certificate(
+                Certificate::from_der(&data)
+                    .map_err(|e| format!("Failed to parse CA bundle '{}': {e}", path.display()))?,
+            );
+        }
+        for pem in certs {
+            builder.add_root_certificate(
+                Certificate::from_pem(&pem)
+                    .map_err(|e| format!("Failed to parse CA bundle '{}': {e}", path.display()))?,
+            );
+        }
+    }
+    match (&tls.cert, &tls.key) {
+        (Some(cert), _) => {
+            builder.identity(load_identity(tls, cert)?);
+        }
+        (None, Some(_)) => return Err("--key requires --cert".into()),
+        (None, None) => {}
+    }
+    if tls.skips_verification() {
+        builder.danger_accept_invalid_certs(true);
+        builder.danger_accept_invalid_hostnames(true);
+    }
+    if let (Some(min), Some(max)) = (tls.min_version, tls.max_version)
+        && min > max
+    {
+        return Err(format!("--tls-min {min} is higher than --tls-max {max}").into());
+    }
+    builder.min_protocol_version(tls.min_version.map(TlsVersion::protocol));
+    builder.max_protocol_version(tls.max_version.map(TlsVersion::protocol));
+    builder.request_alpns(&["http/1.1"]);
+    let http1_connector = builder.build()?;
+    builder.request_alpns(&["h2", "http/1.1"]);
+    Ok(TlsContext {
+        connector: builder.build()?,
+        http1_connector,
+        pins: tls.pins.clone(),
+    })
+}
+
+fn is_https(url: &Parser) -> Result<bool, Box<dyn std::error::Error>> {
+    let https = match url.scheme.as_str() {
+        "https" | "wss" => true,
+        "http" | "ws" => false,
+        other => {
+            return Err(format!(
+                "Unsupported URL scheme '{other}' (use http://, https://, ws:// or wss://)"
+            )
+            .into());
+        }
+    };
+    let host = url.host.as_str();
     if https && (host == "localhost" || host == "127.0.0.1") {
         return Err(
             "Cannot establish HTTPS connection: Server only supports HTTP. Use http:// instead."
@@ -112,27 +878,469 @@ const fn default_port(https: bool) -> u16 {
     if https { 443 } else { 80 }
 }
 
-fn fetch_url(
-    url: &str,
-    timeout: u64,
-    headers: &[String],
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let parsed_url = Parser::parse(url)?;
-    let host = &parsed_url.host;
-    let path = &parsed_url.path;
-    let https = is_https(url, host)?;
-    let port = parsed_url.port.unwrap_or_else(|| default_port(https));
-    fetch_response(host, port, path, timeout, headers, https)
+fn bare_host(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host)
 }
 
-pub fn get_status(
-    url: &str,
-    timeout: u64,
-    headers: &[String],
-) -> Result<u16, Box<dyn std::error::Error>> {
-    parse_http_status(&fetch_url(url, timeout, headers)?)
+// Splits "host:rest" where host may be a bracketed IPv6 literal.
+fn split_host_field(input: &str) -> Option<(&str, &str)> {
+    if input.starts_with('[') {
+        let end = input.find(']')?;
+        let rest = input[end + 1..].strip_prefix(':')?;
+        Some((&input[1..end], rest))
+    } else {
+        input.split_once(':')
+    }
+}
+
+fn parse_port_field(port: &str, option: &str, input: &str) -> Result<Option<u16>, String> {
+    if port.is_empty() {
+        return Ok(None);
+    }
+    port.parse::<u16>()
+        .ok()
+        .filter(|p| *p != 0)
+        .map(Some)
+        .ok_or_else(|| format!("Invalid port '{port}' in {option} '{input}'"))
+}
+
+#[derive(Clone)]
+struct ConnectTo {
+    from_host: String,
+    from_port: Option<u16>,
+    to_host: String,
+    to_port: Option<u16>,
+}
+
+#[derive(Default, Clone)]
+pub struct Routes {
+    resolve: Vec<(String, u16, String)>,
+    connect_to: Vec<ConnectTo>,
+    unix_socket: Option<PathBuf>,
+}
+
+impl Routes {
+    pub const fn is_empty(&self) -> bool {
+        self.resolve.is_empty() && self.connect_to.is_empty() && self.unix_socket.is_none()
+    }
+
+    // Every request goes over this socket instead of TCP, like curl --unix-socket.
+    pub fn set_unix_socket(&mut self, path: PathBuf) -> Result<(), String> {
+        if self.unix_socket.is_some() {
+            return Err("Use either --unix-socket or a unix:// destination, not both".to_string());
+        }
+        self.unix_socket = Some(path);
+        Ok(())
+    }
+
+    // curl syntax: HOST:PORT:ADDR, where ADDR is an IP literal.
+    pub fn add_resolve(&mut self, input: &str) -> Result<(), String> {
+        let invalid = || format!("Invalid --resolve '{input}': expected host:port:address");
+        let (host, rest) = split_host_field(input).ok_or_else(invalid)?;
+        let (port, addr) = rest.split_once(':').ok_or_else(invalid)?;
+        let port = parse_port_field(port, "--resolve", input)?.ok_or_else(invalid)?;
+        if host.is_empty() || parse_scoped_ip(addr).is_none() {
+            return Err(invalid());
+        }
+        self.resolve
+            .push((host.to_ascii_lowercase(), port, bare_host(addr).to_string()));
+        Ok(())
+    }
+
+    // curl syntax: HOST1:PORT1:HOST2:PORT2, where empty fields match any host/port
+    // or keep the original one.
+    pub fn add_connect_to(&mut self, input: &str) -> Result<(), String> {
+        let invalid =
+            || format!("Invalid --connect-to '{input}': expected host1:port1:host2:port2");
+        let (from_host, rest) = split_host_field(input).ok_or_else(invalid)?;
+        let (from_port, rest) = rest.split_once(':').ok_or_else(invalid)?;
+        let (to_host, to_port) = split_host_field(rest).ok_or_else(invalid)?;
+        self.connect_to.push(ConnectTo {
+            from_host: from_host.to_ascii_lowercase(),
+            from_port: parse_port_field(from_port, "--connect-to", input)?,
+            to_host: to_host.to_string(),
+            to_port: parse_port_field(to_port, "--connect-to", input)?,
+        });
+        Ok(())
+    }
+
+    // Returns where to open the TCP connection for host:port; Host and SNI stay unchanged.
+    fn target(&self, host: &str, port: u16) -> (String, u16) {
+        let host = bare_host(host);
+        let (mut target_host, mut target_port) = (host.to_string(), port);
+        if let Some(rule) = self.connect_to.iter().find(|rule| {
+            (rule.from_host.is_empty() || rule.from_host.eq_ignore_ascii_case(host))
+                && rule.from_port.is_none_or(|p| p == port)
+        }) {
+            if !rule.to_host.is_empty() {
+                target_host.clone_from(&rule.to_host);
+            }
+            target_port = rule.to_port.unwrap_or(port);
+        }
+        if let Some((_, _, addr)) = self
+            .resolve
+            .iter()
+            .find(|(name, p, _)| *p == target_port && name.eq_ignore_ascii_case(&target_host))
+        {
+            target_host.clone_from(addr);
+        }
+        (target_host, target_port)
+    }
+}
+
+// Docker style "unix:///var/run/docker.sock:/_ping": the socket path, then the request path.
+// Returns the socket and the http:// URL to request over it. The socket path ends at the
+// first ":/", so paths containing one need --unix-socket.
+pub fn split_unix_url(url: &str) -> Option<(PathBuf, String)> {
+    let rest = url
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("unix://"))
+        .map(|_| &url[7..])?;
+    let (socket, path) = rest
+        .find(":/")
+        .map_or((rest, "/"), |idx| (&rest[..idx], &rest[idx + 1..]));
+    if socket.is_empty() {
+        return None;
+    }
+    Some((PathBuf::from(socket), format!("http://localhost{path}")))
+}
+
+fn format_target(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+type ConnectionKey = (bool, String, u16);
+
+struct Route {
+    target_host: String,
+    target_port: u16,
+    proxy: Option<Proxy>,
+    via: Option<String>,
+    unix_socket: Option<PathBuf>,
+}
+
+pub struct HttpClient {
+    pool: Vec<(ConnectionKey, Connection)>,
+    keep_alive: bool,
+    tls: TlsContext,
+    routes: Routes,
+}
+
+impl HttpClient {
+    pub const fn new(tls: TlsContext, routes: Routes) -> Self {
+        Self {
+            pool: Vec::new(),
+            keep_alive: true,
+            tls,
+            routes,
+        }
+    }
+
+    fn one_shot() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            pool: Vec::new(),
+            keep_alive: false,
+            tls: TlsContext {
+                connector: TlsConnector::new()?,
+                http1_connector: TlsConnector::new()?,
+                pins: Vec::new(),
+            },
+            routes: Routes::default(),
+        })
+    }
+
+    fn route(&self, url: &Parser, https: bool) -> Result<Route, Box<dyn std::error::Error>> {
+        let host = &url.host;
+        let port = url.port.unwrap_or_else(|| default_port(https));
+        if let Some(socket) = &self.routes.unix_socket {
+            if https {
+                return Err(
+                    "HTTPS is not supported over a Unix socket (use http:// or ws://)".into(),
+                );
+            }
+            return Ok(Route {
+                target_host: bare_host(host).to_string(),
+                target_port: port,
+                proxy: None,
+                via: Some(format!("unix socket {}", socket.display())),
+                unix_socket: Some(socket.clone()),
+            });
+        }
+        let (target_host, target_port) = self.routes.target(host, port);
+        let rerouted = (target_host != bare_host(host) || target_port != port)
+            .then(|| format_target(&target_host, target_port));
+        let proxy = proxy::for_url(https, host)?;
+        let via = match (rerouted, &proxy) {
+            (Some(target), Some(proxy)) => Some(format!("{target} through proxy {proxy}")),
+            (Some(target), None) => Some(target),
+            (None, Some(proxy)) => Some(format!("proxy {proxy}")),
+            (None, None) => None,
+        };
+        Ok(Route {
+            target_host,
+            target_port,
+            proxy,
+            via,
+            unix_socket: None,
+        })
+    }
+
+    fn tls_route(&self, url: &str) -> Result<(Parser, Route), Box<dyn std::error::Error>> {
+        let parsed_url = Parser::parse(url)?;
+        if !is_https(&parsed_url)? {
+            return Err("A TLS scan needs an https:// or wss:// URL".into());
+        }
+        let route = self.route(&parsed_url, true)?;
+        Ok((parsed_url, route))
+    }
+
+    // Opens the TCP connection an https:// URL would use, for a hand-made TLS exchange.
+    // Returns the stream and the server name for SNI.
+    pub fn connect_tcp(
+        &self,
+        url: &str,
+        timeout: u64,
+    ) -> Result<(TcpStream, String), Box<dyn std::error::Error>> {
+        let (parsed_url, route) = self.tls_route(url)?;
+        let stream = open_tcp(
+            (route.target_host.as_str(), route.target_port),
+            route.proxy.as_ref(),
+            timeout,
+            &mut Timings::default(),
+        )?;
+        Ok((stream, host_without_zone(&parsed_url.host).to_string()))
+    }
+
+    // Opens a new connection and completes the TLS handshake without sending a request.
+    pub fn handshake(
+        &self,
+        url: &str,
+        timeout: u64,
+    ) -> Result<Timings, Box<dyn std::error::Error>> {
+        let (parsed_url, route) = self.tls_route(url)?;
+        let mut timings = Timings::default();
+        let start = Instant::now();
+        open_connection(
+            &parsed_url.host,
+            (route.target_host.as_str(), route.target_port),
+            route.proxy.as_ref(),
+            timeout,
+            Some(&self.tls),
+            &mut timings,
+        )?;
+        timings.total = start.elapsed();
+        Ok(timings)
+    }
+
+    // Sends a protocol switch request (e.g. a WebSocket handshake) on a new connection
+    // that is never pooled. After a 101 response the connection belongs to the caller.
+    pub fn upgrade(
+        &self,
+        url: &str,
+        timeout: u64,
+        spec: &RequestSpec<'_>,
+    ) -> Result<(HttpReply, Option<Upgraded>), Box<dyn std::error::Error>> {
+        let parsed_url = Parser::parse(url)?;
+        let https = is_https(&parsed_url)?;
+        let route = self.route(&parsed_url, https)?;
+        let head = build_head(&parsed_url, &parsed_url.request_target(), spec, true, None)?;
+        let request_head = head.lines().map(redact_header).collect();
+
+        let start = Instant::now();
+        let mut timings = Timings::default();
+        // Even an HTTP proxy gets a CONNECT tunnel here: the connection outlives the request.
+        let target = (route.target_host.as_str(), route.target_port);
+        let tls = https.then(|| self.tls.http1_only());
+        let mut conn = match &route.unix_socket {
+            Some(socket) => connect_unix(socket, timeout, &mut timings)?,
+            None => open_connection(
+                &parsed_url.host,
+                target,
+                route.proxy.as_ref(),
+                timeout,
+                tls.as_ref(),
+                &mut timings,
+            )?,
+        };
+        // Small frames such as WebSocket pings must not wait for delayed ACKs.
+        if let Some(tcp) = conn.tcp() {
+            tcp.set_nodelay(true)?;
+        }
+        if !conn.send(head.as_bytes(), &mut timings)? {
+            return Err("Connection closed before a response was received".into());
+        }
+        let response = http1::read_response(&mut conn.reader(), false)?;
+        timings.total = start.elapsed();
+        let peer = conn.peer();
+        let protocol = conn.protocol();
+        let upgraded = (response.status == 101).then_some(Upgraded(conn));
+        let (body, encoding) = decode_body(&response.headers, response.body)?;
+        let reply = HttpReply {
+            status: response.status,
+            headers: response.headers,
+            trailers: response.trailers,
+            body,
+            encoding,
+            timings,
+            peer,
+            protocol,
+            via: route.via,
+            request_head,
+        };
+        Ok((reply, upgraded))
+    }
+
+    // An idle pooled connection for this origin that still looks usable.
+    fn take_pooled(&mut self, key: &ConnectionKey, timeout: u64) -> Option<Connection> {
+        let idx = self.pool.iter().position(|(k, _)| k == key)?;
+        let (_, pooled) = self.pool.swap_remove(idx);
+        (pooled.reusable()
+            && pooled
+                .set_read_timeout(Duration::from_millis(timeout))
+                .is_ok())
+        .then_some(pooled)
+    }
+
+    pub fn request(
+        &mut self,
+        url: &str,
+        timeout: u64,
+        spec: &RequestSpec<'_>,
+    ) -> Result<HttpReply, Box<dyn std::error::Error>> {
+        let parsed_url = Parser::parse(url)?;
+        let host = &parsed_url.host;
+        let https = is_https(&parsed_url)?;
+        let port = parsed_url.port.unwrap_or_else(|| default_port(https));
+        let key = (https, host.clone(), port);
+        let Route {
+            target_host,
+            target_port,
+            proxy,
+            via,
+            unix_socket,
+        } = self.route(&parsed_url, https)?;
+
+        // Plain HTTP goes to an HTTP proxy as an absolute-form request; everything else is tunnelled.
+        let forward = proxy
+            .as_ref()
+            .filter(|p| p.kind == ProxyKind::Http && !https);
+        let head = match forward {
+            Some(proxy) => build_head(
+                &parsed_url,
+                &format!(
+                    "http://{}{}",
+                    parsed_url.authority(),
+                    parsed_url.request_target()
+                ),
+                spec,
+                self.keep_alive,
+                proxy.authorization().as_deref(),
+            )?,
+            None => build_head(
+                &parsed_url,
+                &parsed_url.request_target(),
+                spec,
+                self.keep_alive,
+                None,
+            )?,
+        };
+        let mut request = head.as_bytes().to_vec();
+        if let Some(body) = spec.body {
+            request.extend_from_slice(body);
+        }
+        let start = Instant::now();
+
+        let mut timings = Timings::default();
+        // A request on a reused connection is only sent again on a new one if the server
+        // closed it without answering, and never for a method that is not idempotent.
+        let mut answered = None;
+        if let Some(mut conn) = self.take_pooled(&key, timeout) {
+            timings.reused = true;
+            match conn.exchange(&head, &request, spec, timeout, &mut timings)? {
+                Some(response) => answered = Some((response, conn)),
+                None if matches!(spec.method, "GET" | "HEAD" | "OPTIONS") => {}
+                None => return Err("Connection closed before a response was received".into()),
+            }
+        }
+        let (response, conn) = if let Some(answered) = answered {
+            answered
+        } else {
+            timings = Timings::default();
+            let tls = https.then_some(&self.tls);
+            let mut fresh = match (&unix_socket, forward, &proxy) {
+                (Some(socket), _, _) => connect_unix(socket, timeout, &mut timings)?,
+                (None, Some(proxy), _) => {
+                    let target = (proxy.host.as_str(), proxy.port);
+                    open_connection(host, target, None, timeout, tls, &mut timings)?
+                }
+                (None, None, tunnel) => {
+                    let target = (target_host.as_str(), target_port);
+                    open_connection(host, target, tunnel.as_ref(), timeout, tls, &mut timings)?
+                }
+            };
+            let response = fresh
+                .exchange(&head, &request, spec, timeout, &mut timings)?
+                .ok_or("Connection closed before a response was received")?;
+            (response, fresh)
+        };
+        timings.total = start.elapsed();
+        let peer = conn.peer();
+        let protocol = conn.protocol();
+        // Show the request as it went out: HTTP/2 sends pseudo-headers instead of a request line.
+        let request_head = if protocol == Some(HttpVersion::Http2) {
+            http2::request_fields(&head)?
+                .iter()
+                .map(|(name, value)| redact_header(&format!("{name}: {value}")))
+                .collect()
+        } else {
+            head.lines().map(redact_header).collect()
+        };
+        if self.keep_alive && response.keep_alive {
+            self.pool.push((key, conn));
+        }
+        let (body, encoding) = decode_body(&response.headers, response.body)?;
+        Ok(HttpReply {
+            status: response.status,
+            headers: response.headers,
+            trailers: response.trailers,
+            body,
+            encoding,
+            timings,
+            peer,
+            protocol,
+            via,
+            request_head,
+        })
+    }
 }
 
-pub fn get(url: &str, timeout: u64) -> Result<String, Box<dyn std::error::Error>> {
-    parse_http_body(&fetch_url(url, timeout, &[])?)
+// The read timeout surfaces as WouldBlock on Unix and TimedOut on Windows.
+fn read_error(e: std::io::Error, timeout: u64) -> Box<dyn std::error::Error> {
+    if matches!(
+        e.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    ) {
+        format!("No response within {timeout}ms").into()
+    } else {
+        e.into()
+    }
+}
+
+pub fn get(
+    url: &str,
+    timeout: u64,
+    auth: Option<&Credentials>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let spec = RequestSpec {
+        auth,
+        ..RequestSpec::GET
+    };
+    let reply = HttpClient::one_shot()?.request(url, timeout, &spec)?;
+    Ok(String::from_utf8_lossy(&reply.body).into_owned())
 }
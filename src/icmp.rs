@@ -1,8 +1,9 @@
 use crate::colors::Colorize;
 use crate::output::{color_time, micros_to_ms, print_statistics, print_with_prefix};
+use crate::parser::parse_scoped_ip;
 use std::collections::VecDeque;
 use std::error::Error;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
 use std::time::{Duration, Instant};
 
 pub const DEFAULT_ICMP_PAYLOAD: [u8; 24] = [
@@ -13,13 +14,17 @@ pub const DEFAULT_ICMP_PAYLOAD: [u8; 24] = [
 pub const DEFAULT_TTL: u8 = 64;
 pub const DEFAULT_IDENT: u16 = 0;
 
-fn resolve_ip(host: &str) -> std::io::Result<IpAddr> {
-    if let Ok(ip) = host.parse::<IpAddr>() {
-        return Ok(ip);
+fn resolve_ip(host: &str) -> std::io::Result<(IpAddr, u32)> {
+    if let Some(scoped) = parse_scoped_ip(host) {
+        return Ok(scoped);
     }
     let mut addrs = (host, 0).to_socket_addrs()?;
     if let Some(addr) = addrs.next() {
-        return Ok(addr.ip());
+        let scope_id = match addr {
+            SocketAddr::V6(v6) => v6.scope_id(),
+            SocketAddr::V4(_) => 0,
+        };
+        return Ok((addr.ip(), scope_id));
     }
     Err(std::io::Error::new(
         std::io::ErrorKind::AddrNotAvailable,
@@ -29,7 +34,7 @@ fn resolve_ip(host: &str) -> std::io::Result<IpAddr> {
 
 #[cfg(unix)]
 mod platform {
-    use super::{Duration, Instant, Ipv4Addr, Ipv6Addr};
+    use super::{Duration, Instant, IpAddr, Ipv4Addr, Ipv6Addr};
     use std::io;
     use std::mem;
     use std::os::fd::RawFd;
@@ -72,6 +77,7 @@ mod platform {
         Ok(())
     }
 
+    #[allow(clippy::unnecessary_fallible_conversions)]
     fn set_recv_timeout(fd: RawFd, timeout: Duration) -> io::Result<()> {
         let tv = libc::timeval {
             tv_sec: libc::time_t::try_from(timeout.as_secs())
@@ -103,17 +109,36 @@ mod platform {
         )
     }
 
-    fn set_ttl(fd: RawFd, ttl: u8) -> io::Result<()> {
-        setsockopt_int(fd, libc::IPPROTO_IP, libc::IP_TTL, libc::c_int::from(ttl))
+    // Multicast targets take their hop limit from a separate option that defaults to 1.
+    fn set_ttl(fd: RawFd, ttl: u8, multicast: bool) -> io::Result<()> {
+        setsockopt_int(fd, libc::IPPROTO_IP, libc::IP_TTL, libc::c_int::from(ttl))?;
+        if multicast {
+            setsockopt_int(
+                fd,
+                libc::IPPROTO_IP,
+                libc::IP_MULTICAST_TTL,
+                libc::c_int::from(ttl),
+            )?;
+        }
+        Ok(())
     }
 
-    fn set_ttl_v6(fd: RawFd, ttl: u8) -> io::Result<()> {
+    fn set_ttl_v6(fd: RawFd, ttl: u8, multicast: bool) -> io::Result<()> {
         setsockopt_int(
             fd,
             libc::IPPROTO_IPV6,
             libc::IPV6_UNICAST_HOPS,
             libc::c_int::from(ttl),
-        )
+        )?;
+        if multicast {
+            setsockopt_int(
+                fd,
+                libc::IPPROTO_IPV6,
+                libc::IPV6_MULTICAST_HOPS,
+                libc::c_int::from(ttl),
+            )?;
+        }
+        Ok(())
     }
 
     struct FdGuard {
@@ -127,8 +152,202 @@ mod platform {
         }
     }
 
+    fn echo_request(icmp_type: u8, ident: u16, seq: u16, payload: &[u8; 24]) -> Vec<u8> {
+        let mut packet = vec![0u8; 8 + payload.len()];
+        packet[0] = icmp_type;
+        packet[4..6].copy_from_slice(&ident.to_be_bytes());
+        packet[6..8].copy_from_slice(&seq.to_be_bytes());
+        packet[8..].copy_from_slice(payload);
+        if icmp_type == 8 {
+            let csum = icmp_checksum(&packet);
+            packet[2..4].copy_from_slice(&csum.to_be_bytes());
+        }
+        packet
+    }
+
+    fn sockaddr_ip(storage: &libc::sockaddr_storage) -> Option<IpAddr> {
+        match libc::c_int::from(storage.ss_family) {
+            libc::AF_INET => {
+                let sin = unsafe { &*(&raw const *storage).cast::<libc::sockaddr_in>() };
+                Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(
+                    sin.sin_addr.s_addr,
+                ))))
+            }
+            libc::AF_INET6 => {
+                let sin6 = unsafe { &*(&raw const *storage).cast::<libc::sockaddr_in6>() };
+                Some(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)))
+            }
+            _ => None,
+        }
+    }
+
+    fn write_sockaddr(
+        storage: &mut libc::sockaddr_storage,
+        ip: IpAddr,
+        scope_id: u32,
+    ) -> io::Result<libc::socklen_t> {
+        match ip {
+            IpAddr::V4(v4) => {
+                let sin = unsafe { &mut *(&raw mut *storage).cast::<libc::sockaddr_in>() };
+                sin.sin_family = sa_family(libc::AF_INET)?;
+                sin.sin_addr = libc::in_addr {
+                    s_addr: u32::from_be_bytes(v4.octets()).to_be(),
+                };
+                socklen_of::<libc::sockaddr_in>()
+            }
+            IpAddr::V6(v6) => {
+                let sin6 = unsafe { &mut *(&raw mut *storage).cast::<libc::sockaddr_in6>() };
+                sin6.sin6_family = sa_family(libc::AF_INET6)?;
+                sin6.sin6_addr = libc::in6_addr {
+                    s6_addr: v6.octets(),
+                };
+                sin6.sin6_scope_id = scope_id;
+                socklen_of::<libc::sockaddr_in6>()
+            }
+        }
+    }
+
+    fn collect_replies(
+        fd: &FdGuard,
+        v6: bool,
+        check_ids: bool,
+        ident: u16,
+        seq: u16,
+        send_time: Instant,
+        timeout: Duration,
+    ) -> io::Result<Vec<(IpAddr, usize, Duration)>> {
+        let reply_type = if v6 { 129 } else { 0 };
+        let mut replies = Vec::new();
+        let mut buf = vec![0u8; 1500];
+        loop {
+            let remaining = timeout.saturating_sub(send_time.elapsed());
+            if remaining < Duration::from_millis(1) {
+                break;
+            }
+            set_recv_timeout(fd.fd, remaining)?;
+
+            let mut from: libc::sockaddr_storage = unsafe { mem::zeroed() };
+            let mut from_len = socklen_of::<libc::sockaddr_storage>()?;
+            let received = unsafe {
+                libc::recvfrom(
+                    fd.fd,
+                    buf.as_mut_ptr().cast::<libc::c_void>(),
+                    buf.len(),
+                    0,
+                    (&raw mut from).cast::<libc::sockaddr>(),
+                    &raw mut from_len,
+                )
+            };
+            if received < 0 {
+                let err = io::Error::last_os_error();
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) {
+                    break;
+                }
+                continue;
+            }
+            let rtt = send_time.elapsed();
+
+            let n = received.cast_unsigned();
+            let view = &buf[..n];
+            let mut off = 0usize;
+            if !v6 && !view.is_empty() && (view[0] >> 4) == 4 {
+                let ihl = usize::from(view[0] & 0x0f) * 4;
+                if n >= ihl + 8 {
+                    off = ihl;
+                }
+            }
+            if n < off + 8 {
+                continue;
+            }
+            let icmp = &view[off..];
+            let r_id = u16::from_be_bytes([icmp[4], icmp[5]]);
+            let r_seq = u16::from_be_bytes([icmp[6], icmp[7]]);
+            if icmp[0] != reply_type || icmp[1] != 0 || check_ids && (r_id != ident || r_seq != seq)
+            {
+                continue;
+            }
+            if let Some(from_ip) = sockaddr_ip(&from) {
+                replies.push((from_ip, n - off, rtt));
+            }
+        }
+
+        Ok(replies)
+    }
+
+    pub const FIRST_RESPONDER_ONLY: bool = false;
+
+    pub fn ping_collect(
+        ip: IpAddr,
+        scope_id: u32,
+        seq: u16,
+        timeout: Duration,
+        ttl: u8,
+        ident: u16,
+        payload: &[u8; 24],
+    ) -> io::Result<Vec<(IpAddr, usize, Duration)>> {
+        let (family, proto) = if ip.is_ipv6() {
+            (libc::AF_INET6, libc::IPPROTO_ICMPV6)
+        } else {
+            (libc::AF_INET, libc::IPPROTO_ICMP)
+        };
+        let fd_raw = unsafe { libc::socket(family, libc::SOCK_DGRAM, proto) };
+        let is_dgram = fd_raw >= 0;
+        let fd_raw = if fd_raw < 0 {
+            unsafe { libc::socket(family, libc::SOCK_RAW, proto) }
+        } else {
+            fd_raw
+        };
+        if fd_raw < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = FdGuard { fd: fd_raw };
+        // Linux ping sockets rewrite the identifier and only deliver our own replies.
+        let check_ids = !(is_dgram && cfg!(target_os = "linux"));
+
+        if ip.is_ipv6() {
+            let _ = set_ttl_v6(fd.fd, ttl, ip.is_multicast());
+            if scope_id != 0 {
+                setsockopt_int(
+                    fd.fd,
+                    libc::IPPROTO_IPV6,
+                    libc::IPV6_MULTICAST_IF,
+                    libc::c_int::try_from(scope_id)
+                        .map_err(|_| io::Error::other("scope id overflow"))?,
+                )?;
+            }
+        } else {
+            let _ = set_ttl(fd.fd, ttl, ip.is_multicast());
+            setsockopt_int(fd.fd, libc::SOL_SOCKET, libc::SO_BROADCAST, 1)?;
+        }
+
+        let packet = echo_request(if ip.is_ipv6() { 128 } else { 8 }, ident, seq, payload);
+        let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let addr_len = write_sockaddr(&mut addr, ip, scope_id)?;
+
+        let send_time = Instant::now();
+        let sent = unsafe {
+            libc::sendto(
+                fd.fd,
+                packet.as_ptr().cast::<libc::c_void>(),
+                packet.len(),
+                0,
+                (&raw const addr).cast::<libc::sockaddr>(),
+                addr_len,
+            )
+        };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        collect_replies(&fd, ip.is_ipv6(), check_ids, ident, seq, send_time, timeout)
+    }
+
     pub fn ping_once_ipv6(
         ip: Ipv6Addr,
+        scope_id: u32,
         seq: u16,
         timeout: Duration,
         ttl: u8,
@@ -149,7 +368,7 @@ mod platform {
         let fd = FdGuard { fd: fd_raw };
 
         set_recv_timeout(fd.fd, timeout)?;
-        let _ = set_ttl_v6(fd.fd, ttl);
+        let _ = set_ttl_v6(fd.fd, ttl, ip.is_multicast());
 
         let identifier = ident;
 
@@ -170,6 +389,7 @@ mod platform {
         addr.sin6_addr = libc::in6_addr {
             s6_addr: ip.octets(),
         };
+        addr.sin6_scope_id = scope_id;
 
         let addr_ptr = (&raw const addr).cast::<libc::sockaddr>();
         let addr_len = socklen_of::<libc::sockaddr_in6>()?;
@@ -249,7 +469,7 @@ mod platform {
         let fd = FdGuard { fd: fd_raw };
 
         set_recv_timeout(fd.fd, timeout)?;
-        let _ = set_ttl(fd.fd, ttl);
+        let _ = set_ttl(fd.fd, ttl, ip.is_multicast());
 
         let identifier = ident;
 
@@ -343,7 +563,7 @@ mod platform {
 
 #[cfg(windows)]
 mod platform {
-    use super::{Duration, Instant, Ipv4Addr, Ipv6Addr};
+    use super::{Duration, Instant, IpAddr, Ipv4Addr, Ipv6Addr};
     use std::io;
     use std::mem;
     use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
@@ -361,13 +581,25 @@ mod platform {
     }
 
     pub fn ping_once_ipv4(
+        ip: Ipv4Addr,
+        seq: u16,
+        timeout: Duration,
+        ttl: u8,
+        ident: u16,
+        payload: &[u8; 24],
+    ) -> io::Result<(usize, Duration)> {
+        echo_ipv4(ip, seq, timeout, ttl, ident, payload).map(|(_, bytes, rtt)| (bytes, rtt))
+    }
+
+    // Also returns the address the reply came from.
+    fn echo_ipv4(
         ip: Ipv4Addr,
         _seq: u16,
         timeout: Duration,
         _ttl: u8,
         _ident: u16,
         payload: &[u8; 24],
-    ) -> io::Result<(usize, Duration)> {
+    ) -> io::Result<(IpAddr, usize, Duration)> {
         unsafe {
             let handle = IcmpCreateFile();
             if handle == INVALID_HANDLE_VALUE {
@@ -392,21 +624,57 @@ mod platform {
 
             if num > 0 {
                 let rep = std::ptr::read_unaligned(reply_buf.as_ptr().cast::<ICMP_ECHO_REPLY>());
-                Ok((usize::from(rep.DataSize), rtt))
+                let from = Ipv4Addr::from(rep.Address.to_ne_bytes());
+                Ok((IpAddr::V4(from), usize::from(rep.DataSize), rtt))
             } else {
                 Err(io::Error::last_os_error())
             }
         }
     }
 
+    // The ICMP helper API returns a single reply per request, so on Windows only the
+    // first host that answers a multicast or broadcast ping is seen.
+    pub const FIRST_RESPONDER_ONLY: bool = true;
+
+    pub fn ping_collect(
+        ip: IpAddr,
+        scope_id: u32,
+        seq: u16,
+        timeout: Duration,
+        ttl: u8,
+        ident: u16,
+        payload: &[u8; 24],
+    ) -> io::Result<Vec<(IpAddr, usize, Duration)>> {
+        let reply = match ip {
+            IpAddr::V4(v4) => echo_ipv4(v4, seq, timeout, ttl, ident, payload),
+            IpAddr::V6(v6) => echo_ipv6(v6, scope_id, seq, timeout, ttl, ident, payload),
+        };
+        Ok(reply.map_or_else(|_| Vec::new(), |reply| vec![reply]))
+    }
+
     pub fn ping_once_ipv6(
         ip: Ipv6Addr,
+        scope_id: u32,
+        seq: u16,
+        timeout: Duration,
+        ttl: u8,
+        ident: u16,
+        payload: &[u8; 24],
+    ) -> io::Result<(usize, Duration)> {
+        echo_ipv6(ip, scope_id, seq, timeout, ttl, ident, payload)
+            .map(|(_, bytes, rtt)| (bytes, rtt))
+    }
+
+    // Also returns the address the reply came from.
+    fn echo_ipv6(
+        ip: Ipv6Addr,
+        scope_id: u32,
         _seq: u16,
         timeout: Duration,
         _ttl: u8,
         _ident: u16,
         payload: &[u8; 24],
-    ) -> io::Result<(usize, Duration)> {
+    ) -> io::Result<(IpAddr, usize, Duration)> {
         #[repr(C)]
         #[allow(clippy::struct_field_names)]
         struct SockAddrIn6 {
@@ -456,7 +724,7 @@ mod platform {
                 sin6_port: 0,
                 sin6_flowinfo: 0,
                 sin6_addr: ip.octets(),
-                sin6_scope_id: 0,
+                sin6_scope_id: scope_id,
             };
 
             // ICMPV6_ECHO_REPLY_LH (36 bytes) + payload + 8 bytes for ICMP error message + IO_STATUS_BLOCK (16 bytes on 64-bit)
@@ -481,7 +749,11 @@ mod platform {
             let rtt = start.elapsed();
 
             if num > 0 {
-                Ok((payload.len(), rtt))
+                // ICMPV6_ECHO_REPLY starts with a packed IPV6_ADDRESS_EX: port (2 bytes),
+                // flow info (4) and then the replying address.
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(&reply_buf[6..22]);
+                Ok((IpAddr::V6(Ipv6Addr::from(octets)), payload.len(), rtt))
             } else {
                 Err(io::Error::last_os_error())
             }
@@ -491,6 +763,7 @@ mod platform {
 
 pub fn ping_host_once(
     ip: IpAddr,
+    scope_id: u32,
     seq: u16,
     timeout: Duration,
     ttl: u8,
@@ -499,10 +772,125 @@ pub fn ping_host_once(
 ) -> std::io::Result<(usize, Duration)> {
     match ip {
         IpAddr::V4(ipv4) => platform::ping_once_ipv4(ipv4, seq, timeout, ttl, ident, payload),
-        IpAddr::V6(ipv6) => platform::ping_once_ipv6(ipv6, seq, timeout, ttl, ident, payload),
+        IpAddr::V6(ipv6) => {
+            platform::ping_once_ipv6(ipv6, scope_id, seq, timeout, ttl, ident, payload)
+        }
     }
 }
 
+pub const fn is_group_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_multicast() || v4.is_broadcast(),
+        IpAddr::V6(v6) => v6.is_multicast(),
+    }
+}
+
+struct Responder {
+    ip: IpAddr,
+    replies: usize,
+    rtts: Vec<u128>,
+}
+
+fn print_responders(responders: &[Responder], count: usize, minimal: bool) {
+    let header = format!("Responders: {}", responders.len().to_string().green());
+    print_with_prefix(minimal, &header);
+    for r in responders {
+        let min = r.rtts.iter().copied().min().map_or(0.0, micros_to_ms);
+        let total: f64 = r.rtts.iter().copied().map(micros_to_ms).sum();
+        let avg = total / f64::from(u32::try_from(r.rtts.len().max(1)).unwrap_or(u32::MAX));
+        let line = format!(
+            "  {} replies={}/{} min={} avg={}",
+            r.ip.to_string().green(),
+            r.replies,
+            count,
+            color_time(min),
+            color_time(avg)
+        );
+        print_with_prefix(minimal, &line);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn perform_icmp_collect(
+    ip: IpAddr,
+    scope_id: u32,
+    timeout_ms: u64,
+    ttl: u8,
+    ident: u16,
+    count: usize,
+    payload: &[u8; 24],
+    minimal: bool,
+) -> Result<(), Box<dyn Error>> {
+    let timeout = Duration::from_millis(timeout_ms);
+    let mut times: VecDeque<u128> = VecDeque::new();
+    let mut successes = 0usize;
+    let mut responders: Vec<Responder> = Vec::new();
+    let mut seq: u16 = 1;
+    if platform::FIRST_RESPONDER_ONLY {
+        print_with_prefix(
+            minimal,
+            &"Windows reports only the first responder to each request".yellow(),
+        );
+    }
+
+    for attempt_idx in 0..count {
+        let display_seq = attempt_idx + 1;
+        let replies = platform::ping_collect(ip, scope_id, seq, timeout, ttl, ident, payload)?;
+
+        if replies.is_empty() {
+            let msg = format!(
+                "No replies for icmp_seq {display_seq} within {timeout_ms}ms TTL={ttl} Identifier={ident}"
+            );
+            print_with_prefix(minimal, &msg.red());
+        } else {
+            successes += 1;
+        }
+
+        for (from, bytes, rtt) in replies {
+            times.push_back(rtt.as_micros());
+            let msg = format!(
+                "Reply from {}: bytes={} icmp_seq={} time={} TTL={} Identifier={}",
+                from.to_string().green(),
+                bytes,
+                display_seq,
+                color_time(rtt.as_secs_f64() * 1000.0),
+                ttl,
+                ident
+            );
+            print_with_prefix(minimal, &msg);
+            if let Some(r) = responders.iter_mut().find(|r| r.ip == from) {
+                r.replies += 1;
+                r.rtts.push(rtt.as_micros());
+            } else {
+                responders.push(Responder {
+                    ip: from,
+                    replies: 1,
+                    rtts: vec![rtt.as_micros()],
+                });
+            }
+        }
+
+        if display_seq != count {
+            std::thread::sleep(Duration::from_secs(1));
+        }
+        seq = seq.wrapping_add(1);
+    }
+
+    responders.sort_by_key(|r| match r.ip {
+        IpAddr::V4(v4) => (0, u128::from(u32::from(v4))),
+        IpAddr::V6(v6) => (1, u128::from(v6)),
+    });
+    println!();
+    print_responders(&responders, count, minimal);
+    let label = if ip.is_multicast() {
+        "ICMP multicast"
+    } else {
+        "ICMP broadcast"
+    };
+    print_statistics(label, count, successes, &times);
+    Ok(())
+}
+
 pub fn perform_icmp(
     destination: &str,
     timeout_ms: u64,
@@ -512,7 +900,7 @@ pub fn perform_icmp(
     payload: &[u8; 24],
     minimal: bool,
 ) -> Result<(), Box<dyn Error>> {
-    let ip = resolve_ip(destination)?;
+    let (ip, scope_id) = resolve_ip(destination)?;
     let timeout = Duration::from_millis(timeout_ms);
 
     let mut times: VecDeque<u128> = VecDeque::new();
@@ -521,7 +909,7 @@ pub fn perform_icmp(
 
     for attempt_idx in 0..count {
         let start = Instant::now();
-        let result = ping_host_once(ip, seq, timeout, ttl, ident, payload);
+        let result = ping_host_once(ip, scope_id, seq, timeout, ttl, ident, payload);
         let elapsed_us = start.elapsed().as_micros();
         let display_seq = attempt_idx + 1;
 
@@ -0,0 +1,435 @@
+const MAX_BITS: usize = 15;
+const MAX_OUTPUT: usize = 64 * 1024 * 1024;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+// Order in which code length code lengths are stored in a dynamic block header.
+const CLEN_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buf: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: u32) -> Result<u32, String> {
+        while self.count < n {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or("Compressed data is truncated")?;
+            self.pos += 1;
+            self.buf |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.buf & ((1u32 << n) - 1);
+        self.buf >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    const fn align(&mut self) {
+        self.buf = 0;
+        self.count = 0;
+    }
+}
+
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, String> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[usize::from(len)] += 1;
+        }
+        counts[0] = 0;
+
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err("Invalid Huffman code (oversubscribed)".to_string());
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                let slot = &mut offsets[usize::from(len)];
+                symbols[usize::from(*slot)] =
+                    u16::try_from(symbol).map_err(|_| "Too many Huffman symbols")?;
+                *slot += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader<'_>) -> Result<u16, String> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            code |= i32::try_from(reader.bits(1)?).unwrap_or_default();
+            let count = i32::from(self.counts[len]);
+            if code - count < first {
+                let slot = usize::try_from(index + (code - first)).unwrap_or_default();
+                return self
+                    .symbols
+                    .get(slot)
+                    .copied()
+                    .ok_or_else(|| "Invalid Huffman code".to_string());
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("Invalid Huffman code".to_string())
+    }
+}
+
+fn fixed_tables() -> Result<(Huffman, Huffman), String> {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5u8; 30])?))
+}
+
+fn dynamic_tables(reader: &mut BitReader<'_>) -> Result<(Huffman, Huffman), String> {
+    let nlen = usize::try_from(reader.bits(5)?).unwrap_or_default() + 257;
+    let ndist = usize::try_from(reader.bits(5)?).unwrap_or_default() + 1;
+    let ncode = usize::try_from(reader.bits(4)?).unwrap_or_default() + 4;
+    if nlen > 286 || ndist > 30 {
+        return Err("Invalid dynamic block header".to_string());
+    }
+
+    let mut clens = [0u8; 19];
+    for &slot in &CLEN_ORDER[..ncode] {
+        clens[slot] = u8::try_from(reader.bits(3)?).unwrap_or_default();
+    }
+    let clen_code = Huffman::new(&clens)?;
+
+    let mut lengths = vec![0u8; nlen + ndist];
+    let mut i = 0;
+    while i < lengths.len() {
+        let symbol = clen_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (u8::try_from(symbol).unwrap_or_default(), 1),
+            16 => {
+                let prev = *lengths[..i]
+                    .last()
+                    .ok_or("Repeat code with no previous length")?;
+                (prev, 3 + reader.bits(2)?)
+            }
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        let repeat = usize::try_from(repeat).unwrap_or_default();
+        if i + repeat > lengths.len() {
+            return Err("Code lengths overflow the dynamic block".to_string());
+        }
+        lengths[i..i + repeat].fill(value);
+        i += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err("Dynamic block has no end-of-block code".to_string());
+    }
+
+    Ok((
+        Huffman::new(&lengths[..nlen])?,
+        Huffman::new(&lengths[nlen..])?,
+    ))
+}
+
+fn inflate_block(
+    reader: &mut BitReader<'_>,
+    out: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = usize::from(lit.decode(reader)?);
+        match symbol {
+            0..=255 => out.push(u8::try_from(symbol).unwrap_or_default()),
+            256 => return Ok(()),
+            _ => {
+                let idx = symbol - 257;
+                if idx >= LENGTH_BASE.len() {
+                    return Err("Invalid length code".to_string());
+                }
+                let extra = reader.bits(u32::from(LENGTH_EXTRA[idx]))?;
+                let len = usize::from(LENGTH_BASE[idx]) + usize::try_from(extra).unwrap_or(0);
+
+                let didx = usize::from(dist.decode(reader)?);
+                if didx >= DIST_BASE.len() {
+                    return Err("Invalid distance code".to_string());
+                }
+                let extra = reader.bits(u32::from(DIST_EXTRA[didx]))?;
+                let distance = usize::from(DIST_BASE[didx]) + usize::try_from(extra).unwrap_or(0);
+                if distance > out.len() {
+                    return Err("Distance points before the start of the output".to_string());
+                }
+                let start = out.len() - distance;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+        }
+        if out.len() > MAX_OUTPUT {
+            return Err("Decompressed body exceeds 64 MiB".to_string());
+        }
+    }
+}
+
+// Returns the inflated data and the number of input bytes consumed.
+pub fn inflate(data: &[u8]) -> Result<(Vec<u8>, usize), String> {
+    let mut reader = BitReader {
+        data,
+        pos: 0,
+        buf: 0,
+        count: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let header = data
+                    .get(reader.pos..reader.pos + 4)
+                    .ok_or("Compressed data is truncated")?;
+                let len = usize::from(u16::from_le_bytes([header[0], header[1]]));
+                let nlen = u16::from_le_bytes([header[2], header[3]]);
+                if usize::from(!nlen) != len {
+                    return Err("Stored block length check failed".to_string());
+                }
+                reader.pos += 4;
+                let stored = data
+                    .get(reader.pos..reader.pos + len)
+                    .ok_or("Compressed data is truncated")?;
+                out.extend_from_slice(stored);
+                reader.pos += len;
+                if out.len() > MAX_OUTPUT {
+                    return Err("Decompressed body exceeds 64 MiB".to_string());
+                }
+            }
+            1 => {
+                let (lit, dist) = fixed_tables()?;
+                inflate_block(&mut reader, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(&mut reader)?;
+                inflate_block(&mut reader, &mut out, &lit, &dist)?;
+            }
+            _ => return Err("Invalid deflate block type".to_string()),
+        }
+        if last {
+            return Ok((out, reader.pos));
+        }
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the longest run that cannot overflow before the modulo.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 18 || data[0] != 0x1f || data[1] != 0x8b || data[2] != 8 {
+        return Err("Not a gzip stream".to_string());
+    }
+    let flags = data[3];
+    let mut pos = 10;
+    if flags & 0x04 != 0 {
+        let extra = data.get(pos..pos + 2).ok_or("Truncated gzip header")?;
+        pos += 2 + usize::from(u16::from_le_bytes([extra[0], extra[1]]));
+    }
+    for flag in [0x08, 0x10] {
+        if flags & flag != 0 {
+            let end = data
+                .get(pos..)
+                .and_then(|rest| rest.iter().position(|b| *b == 0))
+                .ok_or("Truncated gzip header")?;
+            pos += end + 1;
+        }
+    }
+    if flags & 0x02 != 0 {
+        pos += 2;
+    }
+
+    let (out, used) = inflate(data.get(pos..).ok_or("Truncated gzip header")?)?;
+    let trailer = data
+        .get(pos + used..pos + used + 8)
+        .ok_or("Missing gzip trailer")?;
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc != crc32(&out) || usize::try_from(size).ok() != Some(out.len()) {
+        return Err("gzip checksum mismatch".to_string());
+    }
+    Ok(out)
+}
+
+// HTTP "deflate" is meant to be zlib-wrapped, but some servers send raw deflate.
+pub fn zlib_or_raw(data: &[u8]) -> Result<Vec<u8>, String> {
+    let zlib = data.len() >= 2
+        && data[0] & 0x0f == 8
+        && (u16::from(data[0]) << 8 | u16::from(data[1])) % 31 == 0;
+    if zlib {
+        if data[1] & 0x20 != 0 {
+            return Err("zlib preset dictionaries are not supported".to_string());
+        }
+        let (out, used) = inflate(&data[2..])?;
+        let trailer = data
+            .get(2 + used..2 + used + 4)
+            .ok_or("Missing zlib trailer")?;
+        if u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]) != adler32(&out) {
+            return Err("zlib checksum mismatch".to_string());
+        }
+        return Ok(out);
+    }
+    inflate(data).map(|(out, _)| out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_OUTPUT, adler32, gunzip, inflate, zlib_or_raw};
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // Vectors produced by zlib's compress2/deflateInit2.
+    const STORED: &str = "7801010500faff68656c6c6f062c0215";
+    const FIXED_RAW: &str = "cb48cdc9c957c8409000";
+    const DYNAMIC: &str = concat!(
+        "78da158b410100301082b20a5eff0a734f0473878d81d432921b36c1ed7120d3",
+        "5ebbac3f9adf0d7c219118a7",
+    );
+    const DYNAMIC_TEXT: &[u8] = b"aeebcdacabbadcdbebccbecabdaabcacaaccabcbbbaceddabbddbebabdbcdbbc";
+
+    #[test]
+    fn stored_block() {
+        let data = unhex(STORED);
+        assert_eq!(data[2] >> 1 & 3, 0);
+        assert_eq!(zlib_or_raw(&data).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn fixed_huffman_block() {
+        let data = unhex(FIXED_RAW);
+        assert_eq!(data[0] >> 1 & 3, 1);
+        assert_eq!(zlib_or_raw(&data).unwrap(), b"hello hello hello");
+        let (_, used) = inflate(&data).unwrap();
+        assert_eq!(used, data.len());
+    }
+
+    #[test]
+    fn dynamic_huffman_block() {
+        let data = unhex(DYNAMIC);
+        assert_eq!(data[2] >> 1 & 3, 2);
+        assert_eq!(zlib_or_raw(&data).unwrap(), DYNAMIC_TEXT);
+    }
+
+    #[test]
+    fn zlib_checksum_is_verified() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        let mut data = unhex(DYNAMIC);
+        *data.last_mut().unwrap() ^= 1;
+        assert!(zlib_or_raw(&data).is_err());
+        data.pop();
+        assert!(zlib_or_raw(&data).is_err());
+    }
+
+    #[test]
+    fn gzip_member() {
+        let data = unhex("1f8b0800000000000203cb4dcd2f0700fe6a108a04000000");
+        assert_eq!(gunzip(&data).unwrap(), b"meow");
+        let mut corrupt = data.clone();
+        corrupt[data.len() - 8] ^= 1;
+        assert!(gunzip(&corrupt).is_err());
+    }
+
+    #[test]
+    fn truncated_input() {
+        let data = unhex(DYNAMIC);
+        for len in [3, 10, data.len() - 6] {
+            assert!(inflate(&data[2..len]).is_err(), "length {len}");
+        }
+        assert!(inflate(&[]).is_err());
+        assert!(zlib_or_raw(&unhex(STORED)[..9]).is_err());
+    }
+
+    #[test]
+    fn output_is_capped() {
+        // A fixed block of one literal followed by endless 258-byte copies at distance 1.
+        let mut bits: Vec<bool> = vec![true, true, false];
+        let mut code = |value: u32, len: u32| {
+            bits.extend((0..len).rev().map(|i| value >> i & 1 == 1));
+        };
+        code(0b0011_0000, 8);
+        for _ in 0..=MAX_OUTPUT / 258 {
+            code(0b1100_0101, 8);
+            code(0, 5);
+        }
+        let data: Vec<u8> = bits
+            .chunks(8)
+            .map(|byte| {
+                byte.iter()
+                    .rev()
+                    .fold(0, |acc, &bit| acc << 1 | u8::from(bit))
+            })
+            .collect();
+        assert_eq!(
+            inflate(&data).unwrap_err(),
+            "Decompressed body exceeds 64 MiB"
+        );
+    }
+}
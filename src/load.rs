@@ -0,0 +1,147 @@
+use crate::colors::Colorize;
+use crate::http_check::{HttpOptions, redact_url, simplify_error, with_prefix};
+use crate::https::{HttpClient, RequestSpec};
+use crate::output::{micros_to_ms, print_statistics};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy)]
+pub struct LoadOptions {
+    pub concurrency: usize,
+    pub requests: usize,
+}
+
+struct Outcome {
+    // "HTTP 200", "HTTP 503", "Connection refused", ...
+    label: String,
+    success: bool,
+    latency: Duration,
+}
+
+fn send_one(client: &mut HttpClient, url: &str, opts: &HttpOptions<'_>) -> Outcome {
+    let spec = RequestSpec {
+        method: opts.method,
+        headers: opts.headers,
+        body: opts.body,
+        compressed: opts.compressed,
+        auth: opts.auth,
+    };
+    let start = Instant::now();
+    match client.request(url, opts.timeout, &spec) {
+        Ok(reply) => {
+            let latency = start.elapsed();
+            let status_ok = opts.expect.has_status() || !(500..=599).contains(&reply.status);
+            let passed = opts.expect.failures(&reply, latency).is_empty();
+            let label = if status_ok && !passed {
+                format!("HTTP {} (failed assertion)", reply.status)
+            } else {
+                format!("HTTP {}", reply.status)
+            };
+            Outcome {
+                label,
+                success: status_ok && passed,
+                latency,
+            }
+        }
+        Err(e) => Outcome {
+            label: simplify_error(&e.to_string()).to_string(),
+            success: false,
+            latency: start.elapsed(),
+        },
+    }
+}
+
+// Nearest-rank percentile of an ascending list.
+fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+fn ms(duration: Duration) -> String {
+    format!("{:.2}ms", micros_to_ms(duration.as_micros())).bright_blue()
+}
+
+pub fn perform_http_load(url: &str, opts: &HttpOptions<'_>, load: LoadOptions) {
+    let workers = load.concurrency.clamp(1, load.requests.max(1));
+    println!(
+        "{}",
+        with_prefix(
+            opts.minimal,
+            &format!(
+                "Load testing {} with {} requests, concurrency {workers}",
+                redact_url(url),
+                load.requests
+            )
+        )
+    );
+
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let tx = tx.clone();
+            let next = &next;
+            scope.spawn(move || {
+                // Each worker keeps its own keep-alive connection, like a separate client.
+                let mut client = HttpClient::new(opts.tls.clone(), opts.routes.clone());
+                while next.fetch_add(1, Ordering::Relaxed) < load.requests {
+                    if tx.send(send_one(&mut client, url, opts)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    drop(tx);
+    let wall = start.elapsed();
+
+    let outcomes: Vec<Outcome> = rx.into_iter().collect();
+    let successes = outcomes.iter().filter(|o| o.success).count();
+    let times: VecDeque<u128> = outcomes
+        .iter()
+        .map(|o| if o.success { o.latency.as_micros() } else { 0 })
+        .collect();
+    print_statistics("HTTP", outcomes.len(), successes, &times);
+
+    let rate = f64::from(u32::try_from(outcomes.len()).unwrap_or(u32::MAX)) / wall.as_secs_f64();
+    println!("Throughput:");
+    println!(
+        "\t{} in {}, {}",
+        format!("{} requests", outcomes.len()).bright_blue(),
+        format!("{:.2}s", wall.as_secs_f64()).bright_blue(),
+        format!("{rate:.1} req/s").bright_blue()
+    );
+
+    let mut breakdown: BTreeMap<&str, usize> = BTreeMap::new();
+    for outcome in &outcomes {
+        *breakdown.entry(outcome.label.as_str()).or_default() += 1;
+    }
+    let mut breakdown: Vec<_> = breakdown.into_iter().collect();
+    breakdown.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    println!("Responses:");
+    for (label, count) in breakdown {
+        println!("\t{label} = {}", count.to_string().bright_blue());
+    }
+
+    let mut latencies: Vec<Duration> = outcomes
+        .iter()
+        .filter(|o| o.success)
+        .map(|o| o.latency)
+        .collect();
+    if latencies.is_empty() {
+        return;
+    }
+    latencies.sort_unstable();
+    println!("Latency percentiles (successful requests):");
+    println!(
+        "\tp50 = {}, p90 = {}, p95 = {}, p99 = {}",
+        ms(percentile(&latencies, 50)),
+        ms(percentile(&latencies, 90)),
+        ms(percentile(&latencies, 95)),
+        ms(percentile(&latencies, 99))
+    );
+}
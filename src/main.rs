@@ -1,65 +1,110 @@
 #![deny(warnings)]
 #![warn(clippy::pedantic, clippy::nursery)]
 
-use std::{error::Error, net::IpAddr};
+use std::{error::Error, net::IpAddr, path::PathBuf, sync::Mutex, time::Duration};
 
+mod audit;
 mod cli;
 mod colors;
 mod config;
+mod digest;
+mod encoding;
+mod expect;
+mod har;
+mod hpack;
+mod http1;
+mod http2;
 mod http_check;
 mod https;
 mod icmp;
+mod inflate;
+mod load;
 mod multiport;
 mod output;
 mod parser;
+mod proxy;
+mod quic;
+mod regex;
 mod subnet;
 mod tcp;
+mod tls_scan;
 mod udp;
+mod watch;
+mod websocket;
+mod x509;
 
 use cli::{Arguments, OptionalFlagValue};
 use colors::Colorize;
-use http_check::perform_http_check;
-use icmp::{DEFAULT_ICMP_PAYLOAD, DEFAULT_IDENT, DEFAULT_TTL, perform_icmp};
-use parser::{Extracted, Parser, parse_multiple_destinations, parse_ports};
+use expect::{BodyMatcher, Expectations, parse_header_expectation, parse_status_set};
+use har::HarLog;
+use http_check::{HttpOptions, perform_http_check};
+use https::{Credentials, Routes, TlsContext, TlsOptions, TlsVersion};
+use icmp::{
+    DEFAULT_ICMP_PAYLOAD, DEFAULT_IDENT, DEFAULT_TTL, is_group_address, perform_icmp,
+    perform_icmp_collect,
+};
+use load::{LoadOptions, perform_http_load};
+use parser::{
+    Extracted, Parser, parse_multiple_destinations, parse_ports, parse_scoped_ip, unbracket,
+};
+use proxy::Proxy;
+use quic::perform_quic;
+use regex::Regex;
 use subnet::{
     Ipv4Subnet, Ipv6Subnet, perform_icmp_ipv6_subnet_scan, perform_icmp_subnet_scan,
     perform_tcp_ipv6_subnet_scan, perform_tcp_subnet_scan, perform_udp_ipv6_subnet_scan,
     perform_udp_subnet_scan,
 };
-use tcp::{perform_tcp, perform_tcp_multi_scan};
+use tcp::{perform_tcp, perform_tcp_multi_scan, perform_tcp_via_proxy};
+use tls_scan::perform_tls_scan;
 use udp::{perform_udp, perform_udp_multi_scan};
+use watch::WatchOptions;
+use websocket::perform_websocket_check;
 
 #[cfg(target_os = "windows")]
 use colors::fix_ansicolor;
 
+fn http_url(destination: &str) -> String {
+    if destination.contains("://") {
+        return destination.to_string();
+    }
+    match parse_scoped_ip(destination) {
+        Some((IpAddr::V6(_), _)) if !destination.starts_with('[') => {
+            format!("http://[{destination}]")
+        }
+        _ => format!("http://{destination}"),
+    }
+}
+
+fn is_websocket_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    lower.starts_with("ws://") || lower.starts_with("wss://")
+}
+
 #[inline(never)]
 fn handle_http_check(
     destinations: &[String],
     destination_input: &str,
-    timeout: u64,
-    count: usize,
-    minimal: bool,
     is_multi: bool,
-    headers: &[String],
+    opts: &HttpOptions<'_>,
 ) {
+    let check = |url: &str| {
+        if let Some(tls) = opts.tls_scan {
+            perform_tls_scan(url, opts, tls);
+        } else if let Some(load) = opts.load {
+            perform_http_load(url, opts, load);
+        } else if is_websocket_url(url) {
+            perform_websocket_check(url, opts);
+        } else {
+            perform_http_check(url, opts);
+        }
+    };
     if is_multi {
         for url in destinations {
-            let url = if !url.starts_with("http://") && !url.starts_with("https://") {
-                format!("http://{url}")
-            } else {
-                url.clone()
-            };
-            perform_http_check(&url, timeout, count, minimal, headers);
+            check(&http_url(url));
         }
     } else {
-        let url = if !destination_input.starts_with("http://")
-            && !destination_input.starts_with("https://")
-        {
-            format!("http://{destination_input}")
-        } else {
-            destination_input.to_string()
-        };
-        perform_http_check(&url, timeout, count, minimal, headers);
+        check(&http_url(destination_input));
     }
 }
 
@@ -118,14 +163,9 @@ fn handle_subnet_scan(
 
 #[inline(never)]
 fn resolve_destination(dest: &str, minimal: bool) -> Option<String> {
-    let clean = if dest.starts_with('[') && dest.ends_with(']') {
-        &dest[1..dest.len() - 1]
-    } else {
-        dest
-    };
-
-    if clean.parse::<IpAddr>().is_ok() {
-        Some(clean.to_string())
+    let clean = unbracket(dest);
+    if parse_scoped_ip(&clean).is_some() {
+        Some(clean)
     } else {
         match Parser::extract_url(dest) {
             Extracted::Error => {
@@ -167,21 +207,77 @@ fn handle_multi_icmp(destinations: &[String], timeout: u64, count: usize, minima
 }
 
 #[inline(never)]
-fn handle_single_destination(
-    destination_input: &str,
-    port: Option<u16>,
-    udp: bool,
+fn handle_multi_quic(
+    destinations: &[String],
+    port: u16,
     timeout: u64,
     count: usize,
     minimal: bool,
     no_asn: bool,
-) -> Result<(), Box<dyn Error>> {
-    let destination = resolve_destination(destination_input, minimal)
+) {
+    for dest in destinations {
+        if let Some(destination) = resolve_destination(dest, minimal) {
+            if !minimal {
+                println!(
+                    "\n{} Scanning host: {}",
+                    "[MEOWPING]".magenta(),
+                    destination.green()
+                );
+            }
+            if let Err(e) = perform_quic(&destination, port, timeout, count, minimal, no_asn) {
+                output::print_with_prefix(minimal, &e.to_string());
+            }
+        }
+    }
+}
+
+fn collect_target(destination: &str, force: bool) -> Result<Option<(IpAddr, u32)>, Box<dyn Error>> {
+    match parse_scoped_ip(destination) {
+        Some((ip, scope_id)) if force || is_group_address(ip) => Ok(Some((ip, scope_id))),
+        None if force => Err(
+            "Broadcast/multicast ping requires an IP address (e.g. 224.0.0.1 or ff02::1%eth0)"
+                .into(),
+        ),
+        _ => Ok(None),
+    }
+}
+
+#[inline(never)]
+fn handle_single_destination(ctx: &ProbeCtx<'_>, port: Option<u16>) -> Result<(), Box<dyn Error>> {
+    let ProbeCtx {
+        udp,
+        timeout,
+        count,
+        minimal,
+        no_asn,
+        ..
+    } = *ctx;
+    if let Some(p) = port
+        && !udp
+    {
+        // The proxy resolves the name, so skip the local DNS lookup.
+        let host = Parser::parse(ctx.destination_input).map_or_else(
+            |_| {
+                ctx.destination_input
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_string()
+            },
+            |url| url.host,
+        );
+        if let Some(proxy) = proxy::for_tcp(&host) {
+            return perform_tcp_via_proxy(&host, p, proxy, timeout, count, minimal);
+        }
+    }
+
+    let destination = resolve_destination(ctx.destination_input, minimal)
         .ok_or("DNS Lookup of domain failed: Invalid host or URL")?;
 
     match port {
         Some(p) => {
-            if udp {
+            if ctx.quic {
+                perform_quic(&destination, p, timeout, count, minimal, no_asn)?;
+            } else if udp {
                 perform_udp(&destination, p, timeout, count, minimal, no_asn)?;
             } else {
                 perform_tcp(&destination, p, timeout, count, minimal, no_asn)?;
@@ -247,6 +343,401 @@ fn parse_count(args: &mut Arguments) -> Result<(usize, bool), Box<dyn Error>> {
     }
 }
 
+#[allow(clippy::struct_excessive_bools)]
+struct RequestArgs {
+    method: Option<String>,
+    headers: Vec<String>,
+    body: Option<Vec<u8>>,
+    compressed: bool,
+    follow: Option<usize>,
+    expect: Expectations,
+    tls: TlsOptions,
+    routes: Routes,
+    verbose: bool,
+    auth: Option<Credentials>,
+    load: Option<LoadOptions>,
+    watch: Option<WatchOptions>,
+    audit: bool,
+    har: Option<PathBuf>,
+    tls_scan: bool,
+}
+
+impl RequestArgs {
+    const fn is_set(&self) -> bool {
+        self.method.is_some()
+            || !self.headers.is_empty()
+            || self.body.is_some()
+            || self.compressed
+            || self.follow.is_some()
+            || !self.expect.is_empty()
+            || self.tls.is_set()
+            || !self.routes.is_empty()
+            || self.verbose
+            || self.load.is_some()
+            || self.watch.is_some()
+            || self.audit
+            || self.har.is_some()
+            || self.tls_scan
+    }
+
+    // Method, body, redirects and assertions belong to plain HTTP requests, not WebSocket upgrades.
+    fn check_websocket_options(&self, destination: &str) -> Result<(), Box<dyn Error>> {
+        if !is_websocket_url(destination) {
+            return Ok(());
+        }
+        if self.method.is_some()
+            || self.body.is_some()
+            || self.compressed
+            || self.follow.is_some()
+            || !self.expect.is_empty()
+            || self.load.is_some()
+            || self.watch.is_some()
+            || self.audit
+            || self.har.is_some()
+        {
+            return Err(
+                "-X, --data, --compressed, --follow, --expect-*, --watch-content, --audit, --har and load tests do not apply to ws:// and wss:// checks"
+                    .into(),
+            );
+        }
+        Ok(())
+    }
+
+    fn http_options<'a>(
+        &'a self,
+        setup: &'a HttpSetup,
+        timeout: u64,
+        count: usize,
+        minimal: bool,
+    ) -> HttpOptions<'a> {
+        let default_method = if self.body.is_some() { "POST" } else { "GET" };
+        HttpOptions {
+            timeout,
+            count,
+            minimal,
+            method: self.method.as_deref().unwrap_or(default_method),
+            headers: &setup.headers,
+            body: self.body.as_deref(),
+            compressed: self.compressed,
+            follow: self.follow,
+            expect: &self.expect,
+            tls: &setup.context,
+            routes: &self.routes,
+            verbose: self.verbose,
+            auth: self.auth.as_ref(),
+            load: self.load,
+            watch: self.watch.as_ref(),
+            audit: self.audit,
+            har: setup.har.as_ref(),
+            tls_scan: self.tls_scan.then_some(&setup.tls),
+        }
+    }
+}
+
+fn parse_request_args(args: &mut Arguments) -> Result<RequestArgs, Box<dyn Error>> {
+    let method = args
+        .opt_value_from_str::<String, 2>(["-X", "--request"])
+        .map_err(|_| "Failed to parse request method argument")?
+        .map(|method| {
+            if !method.is_empty() && method.bytes().all(|b| b.is_ascii_alphabetic()) {
+                Ok(method.to_ascii_uppercase())
+            } else {
+                Err(format!("Invalid HTTP method '{method}'"))
+            }
+        })
+        .transpose()?;
+
+    let headers: Vec<String> = args
+        .values_from_str(["-H", "--header"])
+        .map_err(|_| "Failed to parse header argument")?;
+    if let Some(bad) = headers.iter().find(|h| !h.contains(':')) {
+        return Err(format!("Invalid header '{bad}': expected 'Name: value'").into());
+    }
+
+    let data = args
+        .opt_value_from_str::<String, 1>(["--data"])
+        .map_err(|_| "Failed to parse data argument")?;
+    let data_file = args
+        .opt_value_from_str::<String, 1>(["--data-file"])
+        .map_err(|_| "Failed to parse data-file argument")?;
+    let body = match (data, data_file) {
+        (Some(_), Some(_)) => return Err("Use either --data or --data-file, not both".into()),
+        (Some(data), None) => Some(data.into_bytes()),
+        (None, Some(path)) => Some(
+            std::fs::read(&path).map_err(|e| format!("Failed to read data file '{path}': {e}"))?,
+        ),
+        (None, None) => None,
+    };
+
+    let request = RequestArgs {
+        method,
+        headers,
+        body,
+        compressed: args.contains(["--compressed"]),
+        follow: parse_follow(args)?,
+        expect: parse_expectations(args)?,
+        tls: parse_tls_options(args)?,
+        routes: parse_routes(args)?,
+        verbose: args.contains(["-v", "--verbose"]),
+        auth: parse_credentials(args)?,
+        load: parse_load(args)?,
+        watch: parse_watch(args)?,
+        audit: args.contains(["--audit"]),
+        har: args
+            .opt_value_from_str::<String, 1>(["--har"])
+            .map_err(|_| "Failed to parse har argument")?
+            .map(PathBuf::from),
+        tls_scan: args.contains(["--tls-scan"]),
+    };
+    if request.load.is_some()
+        && (request.follow.is_some()
+            || request.watch.is_some()
+            || request.audit
+            || request.har.is_some()
+            || request.tls_scan)
+    {
+        return Err(
+            "--follow, --watch-content, --audit, --har and --tls-scan are not supported in load tests (--concurrency/--requests)"
+                .into(),
+        );
+    }
+    Ok(request)
+}
+
+fn parse_watch(args: &mut Arguments) -> Result<Option<WatchOptions>, Box<dyn Error>> {
+    let watch = args.contains(["--watch-content"]);
+    let diff = args.contains(["--diff"]);
+    let ignore = args
+        .values_from_str::<String, 1>(["--ignore"])
+        .map_err(|_| "Failed to parse ignore argument")?
+        .iter()
+        .map(|pattern| {
+            Regex::with_flags(pattern, false)
+                .map_err(|e| format!("Invalid --ignore pattern '{pattern}': {e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if !watch {
+        if diff || !ignore.is_empty() {
+            return Err("--diff and --ignore require --watch-content".into());
+        }
+        return Ok(None);
+    }
+    Ok(Some(WatchOptions { ignore, diff }))
+}
+
+const DEFAULT_LOAD_REQUESTS: usize = 100;
+
+fn parse_load(args: &mut Arguments) -> Result<Option<LoadOptions>, Box<dyn Error>> {
+    let concurrency = args
+        .opt_value_from_str::<usize, 1>(["--concurrency"])
+        .map_err(|_| "Failed to parse concurrency argument")?;
+    let requests = args
+        .opt_value_from_str::<usize, 1>(["--requests"])
+        .map_err(|_| "Failed to parse requests argument")?;
+    if concurrency.is_none() && requests.is_none() {
+        return Ok(None);
+    }
+    let load = LoadOptions {
+        concurrency: concurrency.unwrap_or(1),
+        requests: requests.unwrap_or(DEFAULT_LOAD_REQUESTS),
+    };
+    if load.concurrency == 0 || load.requests == 0 {
+        return Err("--concurrency and --requests must be at least 1".into());
+    }
+    Ok(Some(load))
+}
+
+fn parse_credentials(args: &mut Arguments) -> Result<Option<Credentials>, Box<dyn Error>> {
+    let user = args
+        .opt_value_from_str::<String, 1>(["--user"])
+        .map_err(|_| "Failed to parse user argument")?;
+    let token = args
+        .opt_value_from_str::<String, 1>(["--bearer-token"])
+        .map_err(|_| "Failed to parse bearer-token argument")?;
+    let token_file = args
+        .opt_value_from_str::<String, 1>(["--token-file"])
+        .map_err(|_| "Failed to parse token-file argument")?;
+    match (user, token, token_file) {
+        (None, None, None) => Ok(None),
+        (Some(user), None, None) => {
+            let (user, password) = user
+                .split_once(':')
+                .ok_or("Invalid --user value: expected 'user:password'")?;
+            Ok(Some(Credentials::Basic(
+                user.to_string(),
+                password.to_string(),
+            )))
+        }
+        (None, Some(token), None) => Ok(Some(Credentials::Bearer(token))),
+        (None, None, Some(path)) => {
+            let credentials = Credentials::TokenFile(PathBuf::from(path));
+            // Fail early on a missing file; it is read again before every request.
+            credentials.authorization()?;
+            Ok(Some(credentials))
+        }
+        _ => Err("Use only one of --user, --bearer-token and --token-file".into()),
+    }
+}
+
+struct HttpSetup {
+    headers: Vec<String>,
+    // Config and command line TLS options merged, as used to build `context`.
+    tls: TlsOptions,
+    context: TlsContext,
+    har: Option<Mutex<HarLog>>,
+}
+
+fn http_setup(
+    cfg: Option<&config::Config>,
+    request: &RequestArgs,
+) -> Result<HttpSetup, Box<dyn Error>> {
+    let headers = https::merge_headers(
+        cfg.map_or(&[][..], |c| c.http_headers.as_slice()),
+        &request.headers,
+    );
+    let tls = cfg.map_or_else(|| request.tls.clone(), |c| request.tls.clone().or(&c.tls));
+    let context = https::build_tls_context(&tls)?;
+    if tls.skips_verification() {
+        println!(
+            "{} TLS certificate and hostname verification is disabled (--insecure); a successful check does not prove the certificate is valid",
+            "Warning:".yellow()
+        );
+    }
+    let har = request.har.as_deref().map(HarLog::create).transpose()?;
+    Ok(HttpSetup {
+        headers,
+        tls,
+        context,
+        har: har.map(Mutex::new),
+    })
+}
+
+fn parse_tls_options(args: &mut Arguments) -> Result<TlsOptions, Box<dyn Error>> {
+    let mut path = |names: [&str; 1]| -> Result<Option<PathBuf>, Box<dyn Error>> {
+        Ok(args
+            .opt_value_from_str::<String, 1>(names)
+            .map_err(|_| format!("Failed to parse {} argument", names[0]))?
+            .map(PathBuf::from))
+    };
+    let cacert = path(["--cacert"])?;
+    let cert = path(["--cert"])?;
+    let key = path(["--key"])?;
+    let insecure = match (
+        args.contains(["-k", "--insecure"]),
+        args.contains(["--no-insecure"]),
+    ) {
+        (true, true) => return Err("--insecure and --no-insecure cannot be combined".into()),
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        (false, false) => None,
+    };
+    let mut version = |names: [&str; 1]| -> Result<Option<TlsVersion>, Box<dyn Error>> {
+        Ok(args
+            .opt_value_from_str::<String, 1>(names)
+            .map_err(|_| format!("Failed to parse {} argument", names[0]))?
+            .map(|version| TlsVersion::parse(&version))
+            .transpose()?)
+    };
+    let min_version = version(["--tls-min"])?;
+    let max_version = version(["--tls-max"])?;
+    Ok(TlsOptions {
+        cacert,
+        cert,
+        key,
+        cert_password: args
+            .opt_value_from_str(["--cert-password"])
+            .map_err(|_| "Failed to parse cert-password argument")?,
+        insecure,
+        min_version,
+        max_version,
+        pins: args
+            .values_from_str::<String, 1>(["--pin-sha256"])
+            .map_err(|_| "Failed to parse pin-sha256 argument")?
+            .iter()
+            .map(|pin| https::parse_pin(pin))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn parse_routes(args: &mut Arguments) -> Result<Routes, Box<dyn Error>> {
+    let mut routes = Routes::default();
+    for resolve in args
+        .values_from_str::<String, 1>(["--resolve"])
+        .map_err(|_| "Failed to parse resolve argument")?
+    {
+        routes.add_resolve(&resolve)?;
+    }
+    for connect_to in args
+        .values_from_str::<String, 1>(["--connect-to"])
+        .map_err(|_| "Failed to parse connect-to argument")?
+    {
+        routes.add_connect_to(&connect_to)?;
+    }
+    if let Some(socket) = args
+        .opt_value_from_str::<String, 1>(["--unix-socket"])
+        .map_err(|_| "Failed to parse unix-socket argument")?
+    {
+        routes.set_unix_socket(PathBuf::from(socket))?;
+    }
+    Ok(routes)
+}
+
+fn parse_expectations(args: &mut Arguments) -> Result<Expectations, Box<dyn Error>> {
+    let mut expect = Expectations::default();
+    if let Some(statuses) = args
+        .opt_value_from_str::<String, 1>(["--expect-status"])
+        .map_err(|_| "Failed to parse expect-status argument")?
+    {
+        expect.statuses = parse_status_set(&statuses)?;
+        expect.status_label = statuses;
+    }
+    if let Some(body) = args
+        .opt_value_from_str::<String, 1>(["--expect-body"])
+        .map_err(|_| "Failed to parse expect-body argument")?
+    {
+        expect.body = Some((body.clone(), BodyMatcher::parse(&body)?));
+    }
+    expect.headers = args
+        .values_from_str::<String, 1>(["--expect-header"])
+        .map_err(|_| "Failed to parse expect-header argument")?
+        .iter()
+        .map(|header| parse_header_expectation(header))
+        .collect();
+    expect.max_latency = args
+        .opt_value_from_str::<u64, 1>(["--max-latency"])
+        .map_err(|_| "Failed to parse max-latency argument")?
+        .map(Duration::from_millis);
+    Ok(expect)
+}
+
+fn parse_proxy(args: &mut Arguments) -> Result<Option<Proxy>, Box<dyn Error>> {
+    let Some(proxy) = args
+        .opt_value_from_str::<String, 1>(["--proxy"])
+        .map_err(|_| "Failed to parse proxy argument")?
+    else {
+        return Ok(None);
+    };
+    let proxy = Proxy::parse(&proxy)?;
+    proxy::set_explicit(proxy.clone());
+    Ok(Some(proxy))
+}
+
+const DEFAULT_REDIRECT_LIMIT: usize = 10;
+
+fn parse_follow(args: &mut Arguments) -> Result<Option<usize>, Box<dyn Error>> {
+    match args
+        .opt_flag_with_optional_value_from_str::<usize, 1>(["--follow"])
+        .map_err(|_| "Failed to parse follow argument")?
+    {
+        OptionalFlagValue::Present(0) => {
+            Err("--follow needs at least 1 hop (leave it out to report redirects as-is)".into())
+        }
+        OptionalFlagValue::Present(limit) => Ok(Some(limit)),
+        OptionalFlagValue::PresentWithoutValue => Ok(Some(DEFAULT_REDIRECT_LIMIT)),
+        OptionalFlagValue::Missing => Ok(None),
+    }
+}
+
 const MAX_SUBNET_MATRIX: usize = 4096;
 
 #[allow(clippy::struct_excessive_bools)]
@@ -263,6 +754,8 @@ struct ProbeCtx<'a> {
     per_host_attempts: usize,
     minimal: bool,
     no_asn: bool,
+    quic: bool,
+    broadcast: bool,
 }
 
 #[inline(never)]
@@ -283,6 +776,7 @@ fn handle_multiport_subnet(ctx: &ProbeCtx<'_>, port_list: &[u16]) -> Result<bool
         multiport::perform_multiport_subnet(
             &subnet.notation(),
             subnet.iter_hosts().map(IpAddr::V4),
+            0,
             port_list,
             ctx.udp,
             ctx.timeout,
@@ -312,6 +806,7 @@ fn handle_multiport_subnet(ctx: &ProbeCtx<'_>, port_list: &[u16]) -> Result<bool
         multiport::perform_multiport_subnet(
             &ipv6_subnet.notation(),
             ipv6_subnet.iter_hosts().map(IpAddr::V6),
+            ipv6_subnet.scope_id(),
             port_list,
             ctx.udp,
             ctx.timeout,
@@ -362,21 +857,35 @@ fn run_host_dispatch(ctx: &ProbeCtx<'_>, port: Option<u16>) -> Result<(), Box<dy
         None => {
             if ctx.is_multi {
                 handle_multi_icmp(ctx.destinations, ctx.timeout, ctx.count, ctx.minimal);
-            } else {
-                handle_single_destination(
-                    ctx.destination_input,
-                    None,
-                    ctx.udp,
+            } else if let Some((ip, scope_id)) =
+                collect_target(ctx.destination_input, ctx.broadcast)?
+            {
+                perform_icmp_collect(
+                    ip,
+                    scope_id,
                     ctx.timeout,
+                    DEFAULT_TTL,
+                    DEFAULT_IDENT,
                     ctx.count,
+                    &DEFAULT_ICMP_PAYLOAD,
                     ctx.minimal,
-                    ctx.no_asn,
                 )?;
+            } else {
+                handle_single_destination(ctx, None)?;
             }
         }
         Some(p) => {
             if ctx.is_multi {
-                if ctx.udp {
+                if ctx.quic {
+                    handle_multi_quic(
+                        ctx.destinations,
+                        p,
+                        ctx.timeout,
+                        ctx.count,
+                        ctx.minimal,
+                        ctx.no_asn,
+                    );
+                } else if ctx.udp {
                     perform_udp_multi_scan(
                         ctx.destinations,
                         p,
@@ -396,15 +905,7 @@ fn run_host_dispatch(ctx: &ProbeCtx<'_>, port: Option<u16>) -> Result<(), Box<dy
                     );
                 }
             } else {
-                handle_single_destination(
-                    ctx.destination_input,
-                    Some(p),
-                    ctx.udp,
-                    ctx.timeout,
-                    ctx.count,
-                    ctx.minimal,
-                    ctx.no_asn,
-                )?;
+                handle_single_destination(ctx, Some(p))?;
             }
         }
     }
@@ -461,6 +962,38 @@ fn resolve_targets(destination_input: &str) -> ResolvedTargets {
     }
 }
 
+fn quic_ports(
+    ports: Option<Vec<u16>>,
+    is_subnet: bool,
+) -> Result<Option<Vec<u16>>, Box<dyn Error>> {
+    if is_subnet {
+        return Err("QUIC probing is not supported for subnet targets (use -u -p 443)".into());
+    }
+    match ports.as_deref() {
+        None => Ok(Some(vec![443])),
+        Some([_]) => Ok(ports),
+        Some(_) => Err("QUIC probing supports a single port".into()),
+    }
+}
+
+// With -s a unix:// destination becomes an HTTP request over the socket;
+// otherwise the socket itself is returned for a connect probe.
+fn take_unix_destination(
+    destination: &mut String,
+    http_check: bool,
+    request: &mut RequestArgs,
+) -> Result<Option<PathBuf>, Box<dyn Error>> {
+    let Some((socket, url)) = https::split_unix_url(destination) else {
+        return Ok(None);
+    };
+    if !http_check {
+        return Ok(Some(socket));
+    }
+    request.routes.set_unix_socket(socket)?;
+    *destination = url;
+    Ok(None)
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     #[cfg(target_os = "windows")]
     fix_ansicolor::enable_ansi_support();
@@ -479,9 +1012,14 @@ fn main() -> Result<(), Box<dyn Error>> {
         args.contains(["-m", "--minimal"]) || cfg.and_then(|c| c.minimal).unwrap_or(false);
     let http_check = args.contains(["-s", "--http"]);
     let no_asn = args.contains(["-a", "--no-asn"]) || cfg.and_then(|c| c.no_asn).unwrap_or(false);
-    let udp = args.contains(["-u", "--udp"]);
+    let quic = args.contains(["-q", "--quic"]);
+    let mut request = parse_request_args(&mut args)?;
+    let broadcast = args.contains(["-b", "--broadcast"]);
+    let explicit_proxy = parse_proxy(&mut args)?;
+    let udp = args.contains(["-u", "--udp"]) || quic;
 
-    let destination_input = read_destination(&mut args)?;
+    let mut destination_input = read_destination(&mut args)?;
+    let unix_probe = take_unix_destination(&mut destination_input, http_check, &mut request)?;
     let ResolvedTargets {
         destinations,
         is_multi,
@@ -502,29 +1040,51 @@ fn main() -> Result<(), Box<dyn Error>> {
             count
         };
 
-    if http_check {
+    request.check_websocket_options(&destination_input)?;
+    if http_check || is_websocket_url(&destination_input) {
         if subnet_target.is_some() || ipv6_subnet_target.is_some() {
             return Err("HTTP checking is not supported for subnet targets".into());
         }
-        let http_headers = cfg.map_or(&[][..], |c| c.http_headers.as_slice());
-        handle_http_check(
-            &destinations,
-            &destination_input,
-            timeout,
-            count,
-            minimal,
-            is_multi,
-            http_headers,
-        );
+        let setup = http_setup(cfg, &request)?;
+        let opts = request.http_options(&setup, timeout, count, minimal);
+        handle_http_check(&destinations, &destination_input, is_multi, &opts);
         return Ok(());
     }
 
-    let ports: Option<Vec<u16>> = args
+    // Without -s, credentials are for the ipinfo.io ASN lookup.
+    if let Some(auth) = request.auth.take() {
+        tcp::set_asn_credentials(auth);
+    }
+    if request.is_set() {
+        return Err("HTTP request, assertion, TLS and connection options require -s/--http".into());
+    }
+
+    if let Some(socket) = unix_probe {
+        return tcp::perform_unix(&socket, count, minimal);
+    }
+
+    let mut ports: Option<Vec<u16>> = args
         .opt_value_from_str::<String, 2>(["-p", "--port"])
         .map_err(|_| "Failed to parse port argument")?
         .map(|s| parse_ports(&s))
         .transpose()?;
 
+    if quic {
+        ports = quic_ports(
+            ports,
+            subnet_target.is_some() || ipv6_subnet_target.is_some(),
+        )?;
+    }
+
+    let single_tcp_probe = !udp
+        && !is_multi
+        && subnet_target.is_none()
+        && ipv6_subnet_target.is_none()
+        && matches!(ports.as_deref(), Some([_]));
+    if explicit_proxy.is_some() && !single_tcp_probe {
+        return Err("--proxy applies to -s/--http checks and single-port TCP probes (-p)".into());
+    }
+
     if udp && ports.is_none() {
         return Err("UDP probing requires a port (use -p/--port with --udp)".into());
     }
@@ -546,6 +1106,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         per_host_attempts,
         minimal,
         no_asn,
+        quic,
+        broadcast,
     };
 
     run_probe_dispatch(&ctx)?;
@@ -1,18 +1,74 @@
 use crate::colors::Colorize;
 use crate::output::{color_time, micros_to_ms, print_statistics, print_with_prefix};
+use crate::parser::{parse_scoped_ip, scoped_socket_addr};
 use crate::tcp::{fetch_asn, resolve_ip, tcp_connect_once};
-use crate::udp::{ProbeOutcome, probe_payload, udp_probe_once};
+use crate::udp::{IcmpReport, ProbeOutcome, probe_payload, udp_probe_once};
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::thread;
 use std::time::Duration;
 
 const CHUNK: usize = 32;
+const UDP_RETRIES: u32 = 2;
+const UDP_MAX_RETRIES: u32 = 6;
+const UDP_BACKOFF_MS: u64 = 250;
+const UDP_MAX_GAP_MS: u64 = 1000;
+
+struct UdpPacing {
+    width: usize,
+    gap: Duration,
+    retransmits: usize,
+    recovered: usize,
+    rate_limited: bool,
+}
+
+impl UdpPacing {
+    const fn new() -> Self {
+        Self {
+            width: CHUNK,
+            gap: Duration::ZERO,
+            retransmits: 0,
+            recovered: 0,
+            rate_limited: false,
+        }
+    }
+
+    fn slow_down(&mut self) {
+        self.rate_limited = true;
+        self.width = (self.width / 2).max(1);
+        let gap_ms = u64::try_from(self.gap.as_millis()).unwrap_or(UDP_MAX_GAP_MS);
+        self.gap = Duration::from_millis((gap_ms * 2).clamp(10, UDP_MAX_GAP_MS));
+    }
+
+    fn speed_up(&mut self) {
+        self.width = (self.width + 1).min(CHUNK);
+    }
+
+    fn print_summary(&self, minimal: bool) {
+        if self.rate_limited {
+            let message = format!(
+                "ICMP rate limiting detected: {} of {} retransmitted probe(s) came back closed; pacing slowed to {} in flight, {}ms apart. open|filtered results may still hide closed ports",
+                self.recovered,
+                self.retransmits,
+                self.width,
+                self.gap.as_millis()
+            );
+            print_with_prefix(minimal, &message.yellow());
+        } else if self.retransmits > 0 && !minimal {
+            let message = format!(
+                "Retransmitted {} unanswered UDP probe(s); no ICMP rate limiting observed",
+                self.retransmits
+            );
+            print_with_prefix(minimal, &message);
+        }
+    }
+}
 
 #[derive(Clone, Copy)]
 enum PortVerdict {
     Open { rtt_us: u128 },
     Closed,
+    Unreachable(IcmpReport),
     NoResponse,
 }
 
@@ -34,10 +90,12 @@ const fn verdict_from_udp(outcome: ProbeOutcome) -> PortVerdict {
             rtt_us: rtt.as_micros(),
         },
         ProbeOutcome::Closed => PortVerdict::Closed,
+        ProbeOutcome::Unreachable(report) => PortVerdict::Unreachable(report),
         ProbeOutcome::NoResponse => PortVerdict::NoResponse,
     }
 }
 
+#[allow(clippy::too_many_lines)]
 fn format_port_result(res: &PortResult, asn: &str, udp: bool, minimal: bool) -> String {
     let proto = if udp { "UDP" } else { "TCP" };
     let show_asn = !asn.is_empty();
@@ -91,6 +149,28 @@ fn format_port_result(res: &PortResult, asn: &str, udp: bool, minimal: bool) ->
                 )
             }
         }
+        PortVerdict::Unreachable(report) => {
+            if show_asn {
+                format!(
+                    "{}:{} {} ({}): protocol={} port={}",
+                    res.host.red(),
+                    res.port.to_string().red(),
+                    report.describe(),
+                    asn.red(),
+                    proto.red(),
+                    res.port.to_string().red()
+                )
+            } else {
+                format!(
+                    "{}:{} {}: protocol={} port={}",
+                    res.host.red(),
+                    res.port.to_string().red(),
+                    report.describe(),
+                    proto.red(),
+                    res.port.to_string().red()
+                )
+            }
+        }
         PortVerdict::NoResponse => {
             if udp {
                 if show_asn {
@@ -138,6 +218,13 @@ fn format_port_result(res: &PortResult, asn: &str, udp: bool, minimal: bool) ->
 struct ProbeUnit {
     ip: IpAddr,
     port: u16,
+    scope_id: u32,
+}
+
+impl ProbeUnit {
+    const fn addr(self) -> SocketAddr {
+        scoped_socket_addr(self.ip, self.port, self.scope_id)
+    }
 }
 
 fn probe_units_concurrent(
@@ -145,27 +232,27 @@ fn probe_units_concurrent(
     udp: bool,
     timeout_ms: u64,
     payloads: &HashMap<u16, Vec<u8>>,
+    gap: Duration,
 ) -> Vec<PortVerdict> {
     let timeout_dur = Duration::from_millis(timeout_ms);
     let mut handles = Vec::with_capacity(units.len());
-    for &unit in units {
+    for (idx, &unit) in units.iter().enumerate() {
         let payload = if udp {
             payloads.get(&unit.port).cloned()
         } else {
             None
         };
+        if idx > 0 && !gap.is_zero() {
+            thread::sleep(gap);
+        }
         handles.push((
             unit,
             thread::spawn(move || {
                 if udp {
                     let payload = payload.unwrap_or_default();
-                    verdict_from_udp(udp_probe_once(
-                        std::net::SocketAddr::new(unit.ip, unit.port),
-                        &payload,
-                        timeout_dur,
-                    ))
+                    verdict_from_udp(udp_probe_once(unit.addr(), &payload, timeout_dur))
                 } else {
-                    verdict_from_tcp(tcp_connect_once(unit.ip, unit.port, timeout_ms))
+                    verdict_from_tcp(tcp_connect_once(unit.addr(), timeout_ms))
                 }
             }),
         ));
@@ -177,6 +264,82 @@ fn probe_units_concurrent(
         .collect()
 }
 
+fn retransmit_unanswered(
+    units: &[ProbeUnit],
+    verdicts: &mut [PortVerdict],
+    timeout_ms: u64,
+    payloads: &HashMap<u16, Vec<u8>>,
+    pacing: &mut UdpPacing,
+) {
+    let mut recovered = 0usize;
+    let mut recovered_last_round = false;
+    for retry in 0..UDP_MAX_RETRIES {
+        if retry >= UDP_RETRIES && !recovered_last_round {
+            break;
+        }
+        let pending: Vec<usize> = verdicts
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| matches!(v, PortVerdict::NoResponse))
+            .map(|(i, _)| i)
+            .collect();
+        if pending.is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(UDP_BACKOFF_MS << retry.min(2)));
+
+        let mut round_recovered = 0usize;
+        pacing.retransmits += pending.len();
+        for indices in pending.chunks(pacing.width) {
+            let batch: Vec<ProbeUnit> = indices.iter().map(|&i| units[i]).collect();
+            let retried = probe_units_concurrent(&batch, true, timeout_ms, payloads, pacing.gap);
+            for (&i, verdict) in indices.iter().zip(retried) {
+                if matches!(verdict, PortVerdict::Closed) {
+                    round_recovered += 1;
+                }
+                verdicts[i] = verdict;
+            }
+        }
+
+        recovered_last_round = round_recovered > 0;
+        if recovered_last_round {
+            recovered += round_recovered;
+            pacing.slow_down();
+        }
+    }
+
+    pacing.recovered += recovered;
+    if recovered == 0 {
+        pacing.speed_up();
+    }
+}
+
+fn probe_units<F>(
+    units: &[ProbeUnit],
+    udp: bool,
+    timeout_ms: u64,
+    payloads: &HashMap<u16, Vec<u8>>,
+    pacing: &mut UdpPacing,
+    mut on_result: F,
+) where
+    F: FnMut(&ProbeUnit, PortVerdict),
+{
+    let mut offset = 0;
+    while offset < units.len() {
+        let width = if udp { pacing.width } else { CHUNK };
+        let chunk = &units[offset..units.len().min(offset + width)];
+        let gap = if udp { pacing.gap } else { Duration::ZERO };
+        let mut verdicts = probe_units_concurrent(chunk, udp, timeout_ms, payloads, gap);
+        if udp {
+            retransmit_unanswered(chunk, &mut verdicts, timeout_ms, payloads, pacing);
+        }
+        for (unit, verdict) in chunk.iter().zip(verdicts) {
+            on_result(unit, verdict);
+        }
+        offset += chunk.len();
+    }
+}
+
 fn payloads_for(ports: &[u16], udp: bool) -> HashMap<u16, Vec<u8>> {
     let mut map = HashMap::new();
     if udp {
@@ -187,7 +350,7 @@ fn payloads_for(ports: &[u16], udp: bool) -> HashMap<u16, Vec<u8>> {
     map
 }
 
-fn aggregate(results: &[PortResult], ports: &[u16], udp: bool, minimal: bool, proto_label: &str) {
+fn aggregate(results: &[PortResult], ports: &[u16], minimal: bool, proto_label: &str) {
     let mut times: VecDeque<u128> = VecDeque::new();
     let mut successes = 0usize;
     let mut responsive_ports: HashSet<(String, u16)> = HashSet::new();
@@ -226,7 +389,6 @@ fn aggregate(results: &[PortResult], ports: &[u16], udp: bool, minimal: bool, pr
         unique_ports
     );
     print_with_prefix(minimal, &summary);
-    let _ = udp;
     print_statistics(proto_label, total, successes, &times);
 }
 
@@ -242,13 +404,13 @@ pub fn perform_multiport_hosts(
     let attempts = attempts.max(1);
     let payloads = payloads_for(ports, udp);
 
-    let mut resolved: Vec<(String, IpAddr)> = Vec::with_capacity(hosts.len());
+    let mut resolved: Vec<(String, SocketAddr)> = Vec::with_capacity(hosts.len());
     for host in hosts {
         if let Ok(addr) = resolve_ip(host, ports[0]) {
-            if !minimal && addr.ip().to_string() != *host {
+            if !minimal && parse_scoped_ip(host).is_none() {
                 crate::tcp::print_ip_info(host, &addr.ip().to_string(), minimal);
             }
-            resolved.push((host.clone(), addr.ip()));
+            resolved.push((host.clone(), addr));
         } else {
             let message = format!("DNS Lookup of domain failed: Invalid host or URL: {host}");
             print_with_prefix(minimal, &message);
@@ -272,6 +434,7 @@ pub fn perform_multiport_hosts(
     print_with_prefix(minimal, &header);
 
     let mut all_results: Vec<PortResult> = Vec::with_capacity(resolved.len() * ports.len());
+    let mut pacing = UdpPacing::new();
 
     for attempt_idx in 0..attempts {
         if !minimal && attempts > 1 {
@@ -280,40 +443,56 @@ pub fn perform_multiport_hosts(
         }
 
         let mut units: Vec<ProbeUnit> = Vec::with_capacity(resolved.len() * ports.len());
-        for (_, ip) in &resolved {
+        for (_, addr) in &resolved {
+            let scope_id = match addr {
+                SocketAddr::V6(v6) => v6.scope_id(),
+                SocketAddr::V4(_) => 0,
+            };
             for &port in ports {
-                units.push(ProbeUnit { ip: *ip, port });
+                units.push(ProbeUnit {
+                    ip: addr.ip(),
+                    port,
+                    scope_id,
+                });
             }
         }
 
-        for chunk in units.chunks(CHUNK) {
-            let verdicts = probe_units_concurrent(chunk, udp, timeout_ms, &payloads);
-            for (unit, verdict) in chunk.iter().zip(verdicts) {
+        probe_units(
+            &units,
+            udp,
+            timeout_ms,
+            &payloads,
+            &mut pacing,
+            |unit, verdict| {
                 let host = resolved
                     .iter()
-                    .find(|(_, ip)| ip == &unit.ip)
+                    .find(|(_, addr)| addr.ip() == unit.ip)
                     .map_or_else(|| unit.ip.to_string(), |(h, _)| h.clone());
                 let asn = fetch_asn(&unit.ip.to_string(), no_asn, timeout_ms)
                     .unwrap_or_else(|_| "?".to_string());
                 let res = PortResult {
-                    host: host.clone(),
+                    host,
                     port: unit.port,
                     verdict,
                 };
                 let entry = format_port_result(&res, &asn, udp, minimal);
                 println!("{entry}");
                 all_results.push(res);
-            }
-        }
+            },
+        );
     }
 
-    aggregate(&all_results, ports, udp, minimal, proto_label);
+    aggregate(&all_results, ports, minimal, proto_label);
+    if udp {
+        pacing.print_summary(minimal);
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
 pub fn perform_multiport_subnet<I>(
     host_label: &str,
     hosts: I,
+    scope_id: u32,
     ports: &[u16],
     udp: bool,
     timeout_ms: u64,
@@ -357,6 +536,7 @@ pub fn perform_multiport_subnet<I>(
 
     let payloads = payloads_for(ports, udp);
     let mut all_results: Vec<PortResult> = Vec::with_capacity(host_vec.len() * ports.len());
+    let mut pacing = UdpPacing::new();
 
     for attempt_idx in 0..attempts {
         if !minimal && attempts > 1 {
@@ -367,13 +547,17 @@ pub fn perform_multiport_subnet<I>(
         let mut units: Vec<ProbeUnit> = Vec::with_capacity(host_vec.len() * ports.len());
         for &ip in &host_vec {
             for &port in ports {
-                units.push(ProbeUnit { ip, port });
+                units.push(ProbeUnit { ip, port, scope_id });
             }
         }
 
-        for chunk in units.chunks(CHUNK) {
-            let verdicts = probe_units_concurrent(chunk, udp, timeout_ms, &payloads);
-            for (unit, verdict) in chunk.iter().zip(verdicts) {
+        probe_units(
+            &units,
+            udp,
+            timeout_ms,
+            &payloads,
+            &mut pacing,
+            |unit, verdict| {
                 let res = PortResult {
                     host: unit.ip.to_string(),
                     port: unit.port,
@@ -384,9 +568,12 @@ pub fn perform_multiport_subnet<I>(
                     println!("{entry}");
                 }
                 all_results.push(res);
-            }
-        }
+            },
+        );
     }
 
-    aggregate(&all_results, ports, udp, minimal, proto_label);
+    aggregate(&all_results, ports, minimal, proto_label);
+    if udp {
+        pacing.print_summary(minimal);
+    }
 }
@@ -64,6 +64,7 @@ pub fn color_time(time_ms: f64) -> String {
     }
 }
 
+#[allow(clippy::too_many_lines)]
 pub fn print_help() {
     let name = env!("CARGO_PKG_NAME");
     let version = env!("CARGO_PKG_VERSION").bright_blue();
@@ -88,7 +89,98 @@ pub fn print_help() {
     );
     println!("    -m, --minimal             Changes the Prints to be more Minimal");
     println!("    -s, --http              Check if the destination URL is online via HTTP/S");
+    println!(
+        "                            (ws:// and wss:// URLs: WebSocket handshake, then timed ping/pong)"
+    );
+    println!("        --follow[=N]        Follow HTTP redirects, up to N hops (default: 10)");
+    println!(
+        "    -X, --request <method>  HTTP method for -s checks (GET, HEAD, POST, PUT, OPTIONS, ...)"
+    );
+    println!(
+        "    -H, --header <header>   Extra HTTP header 'Name: value' (repeatable, overrides config headers)"
+    );
+    println!("        --data <body>       Send a request body (implies POST unless -X is given)");
+    println!("        --data-file <path>  Send the contents of a file as the request body");
+    println!(
+        "        --compressed        Send 'Accept-Encoding: gzip, deflate' (compressed bodies are always decoded)"
+    );
+    println!(
+        "        --cacert <pem>      Trust the CA certificate(s) in this PEM bundle for HTTPS"
+    );
+    println!(
+        "        --cert <file>       Client certificate: PEM (with --key or an embedded key) or PKCS#12 (.p12/.pfx)"
+    );
+    println!("        --key <pem>         PKCS#8 private key for a PEM --cert");
+    println!("        --cert-password <p> Password for a PKCS#12 --cert");
+    println!(
+        "    -k, --insecure          Skip TLS certificate and hostname verification (prints a warning)"
+    );
+    println!(
+        "        --no-insecure       Verify certificates even if the config file sets insecure = true"
+    );
+    println!(
+        "        --tls-min <ver>     Lowest TLS version to allow for HTTPS/WSS: 1.0, 1.1, 1.2 or 1.3"
+    );
+    println!("        --tls-max <ver>     Highest TLS version to allow for HTTPS/WSS");
+    println!(
+        "        --tls-scan          Report which TLS versions (1.0-1.3) the server accepts instead of checking HTTP"
+    );
+    println!(
+        "        --pin-sha256 <b64>  Require this base64 SHA-256 of the leaf certificate or its public key (repeatable)"
+    );
+    println!(
+        "        --resolve <h:p:ip>  Connect to this address for host:port, keeping Host and SNI (repeatable)"
+    );
+    println!(
+        "        --connect-to <h1:p1:h2:p2> Connect to h2:p2 instead of h1:p1, keeping Host and SNI (repeatable)"
+    );
+    println!(
+        "        --unix-socket <path> Send HTTP checks over this Unix domain socket (or use unix:///path.sock:/url-path)"
+    );
+    println!(
+        "                            unix:// paths end at the first ':/'; use --unix-socket if the path contains one"
+    );
+    println!(
+        "        --proxy <url>       Use an http:// or socks5(h):// proxy for HTTP checks, ASN lookups and TCP probes"
+    );
+    println!(
+        "        --user <user:pass>  Send HTTP Basic credentials (HTTP checks, or ASN lookups without -s)"
+    );
+    println!("        --bearer-token <t>  Send 'Authorization: Bearer <t>'");
+    println!("        --token-file <path> Read the bearer token from a file before every request");
+    println!(
+        "    -v, --verbose           Show the request and response headers (secrets redacted) and certificate fingerprints"
+    );
+    println!(
+        "        --har <file>        Record every HTTP request and response (headers, sizes, timings) as a HAR 1.2 file"
+    );
+    println!(
+        "        --watch-content     Hash each response body and report when the content changes"
+    );
+    println!(
+        "        --ignore <regex>    Remove matches from each line before hashing (repeatable)"
+    );
+    println!("        --diff              Show the changed lines when the content changes");
+    println!(
+        "        --audit             Grade security headers, cookie flags, HTTPS redirect and certificate expiry"
+    );
+    println!(
+        "        --requests <n>      Load test: send n requests as fast as possible (default: 100)"
+    );
+    println!("        --concurrency <n>   Load test: number of parallel workers (default: 1)");
+    println!("        --expect-status <s> Required HTTP status(es): 200,204 or 200-299 or 2xx");
+    println!(
+        "        --expect-body <s>   Required body substring, or a /regex/ (/regex/i ignores case)"
+    );
+    println!("        --expect-header <h> Required header 'Name: value' or 'Name' (repeatable)");
+    println!("        --max-latency <ms>  Fail the check when the request takes longer than this");
+    println!(
+        "    -b, --broadcast         Keep listening after the first reply and list every responder (implied for multicast)"
+    );
     println!("    -u, --udp              Probe a UDP port instead of using TCP (requires -p)");
+    println!(
+        "    -q, --quic             Probe a QUIC endpoint via Version Negotiation (UDP, default port 443)"
+    );
     println!("    -a, --no-asn            Disable ASN/organization lookups (use static data)");
     println!(
         "    -C, --config [path]     Load settings from a config file (default: meowping.conf next to the executable)"
@@ -101,6 +193,11 @@ pub fn print_help() {
     println!("    {name} 8.8.8.8 -c 10");
     println!("    {name} 2606:4700:4700::1111");
 
+    println!("\n  {}:", "Multicast / Broadcast Ping".yellow());
+    println!("    {name} 224.0.0.1 -c 3");
+    println!("    {name} ff02::1%eth0 -c 1");
+    println!("    {name} 192.168.1.255 -b");
+
     println!("\n  {}:", "TCP Port Check".yellow());
     println!("    {name} example.com -p 443");
     println!("    {name} 192.168.1.1 -p 22 -t 2000");
@@ -109,6 +206,10 @@ pub fn print_help() {
     println!("    {name} 1.1.1.1 -p 53 -u");
     println!("    {name} time.google.com -p 123 -u -c 3");
 
+    println!("\n  {}:", "QUIC/HTTP3 Reachability".yellow());
+    println!("    {name} cloudflare.com -q");
+    println!("    {name} example.com -q -p 8443 -c 3");
+
     println!("\n  {}:", "Multi-Port Probe".yellow());
     println!("    {name} 1.1.1.1 -p 53,80,443");
     println!("    {name} example.com -p 22,80,443,8080");
@@ -119,6 +220,23 @@ pub fn print_help() {
     println!("\n  {}:", "HTTP/HTTPS Check".yellow());
     println!("    {name} https://example.com -s");
     println!("    {name} example.com -s -c 5");
+    println!("    {name} http://example.com -s --follow");
+    println!("    {name} https://example.com/health -s -X HEAD");
+    println!(
+        "    {name} https://api.example.com/health -s --expect-status 200 --expect-body '/\"status\":\\s*\"ok\"/'"
+    );
+    println!(
+        "    {name} https://api.example.com/ping -s --data '{{}}' -H 'Content-Type: application/json'"
+    );
+
+    println!("\n  {}:", "TLS Versions".yellow());
+    println!("    {name} https://example.com -s --tls-min 1.2");
+    println!("    {name} https://example.com -s --tls-scan");
+
+    println!("\n  {}:", "Unix Domain Sockets".yellow());
+    println!("    {name} unix:///var/run/docker.sock -c 3");
+    println!("    {name} unix:///var/run/docker.sock:/_ping -s");
+    println!("    {name} http://localhost/healthz -s --unix-socket /run/app/admin.sock");
 
     println!("\n  {}:", "Multi-Ping (Multiple Destinations)".yellow());
     println!("    {name} google.com,cloudflare.com,1.1.1.1 -c 2");
@@ -129,11 +247,12 @@ pub fn print_help() {
     println!("    {name} 10.0.0.0/28 -c 3");
     println!("    {name} 192.168.1.0/24 -p 80");
     println!("    {name} 2001:db8::/120");
-    println!("    {name} fe80::/112 -p 22");
+    println!("    {name} fe80::/112%eth0 -p 22");
 
     println!("\n{}:", "IPv6 Support".bright_blue());
     println!("    MeowPing supports IPv6 addresses for all connection types (ICMP, TCP, HTTP)");
     println!("    IPv6 subnet scanning is supported up to /112 prefix length");
+    println!("    Link-local addresses take a zone ID in every mode: fe80::1%eth0, fe80::/112%2");
 
     println!("\n{}:", "Notes".bright_blue());
     println!("    • Subnet scans default to 1 attempt per host unless -c is specified");
@@ -142,6 +261,15 @@ pub fn print_help() {
     println!(
         "    • UDP probes need no privileges: a response means open, 'Port Unreachable' means closed, and silence is reported as open|filtered"
     );
+    println!(
+        "    • On Linux, other ICMP errors for UDP probes are shown with their type/code and reporting router (e.g. filtered (admin prohibited))"
+    );
+    println!(
+        "    • HTTP checks break each request down into DNS, connect, TLS and time-to-first-byte; statistics use the total time"
+    );
+    println!(
+        "    • Repeated HTTP attempts reuse a keep-alive connection when the server allows it"
+    );
 }
 
 pub fn print_welcome() {
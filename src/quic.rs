@@ -0,0 +1,185 @@
+use crate::colors::Colorize;
+use crate::output::{color_time, print_statistics};
+use crate::parser::parse_scoped_ip;
+use crate::tcp::{fetch_asn, print_ip_info, resolve_ip};
+use crate::udp::{ProbeOutcome, format_udp_status, udp_exchange};
+use std::collections::VecDeque;
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::hash::BuildHasher;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// RFC 9000 requires client Initial datagrams to be at least 1200 bytes, and
+// servers only answer with Version Negotiation for datagrams of that size.
+const INITIAL_DATAGRAM_LEN: usize = 1200;
+// Reserved 0x?a?a?a?a pattern: never a real version, so it always triggers negotiation.
+const GREASE_VERSION: u32 = 0x1a2a_3a4a;
+const CID_LEN: usize = 8;
+const SCID_OFFSET: usize = 1 + 4 + 1 + CID_LEN + 1;
+const CID_LEN_FIELD: u8 = {
+    assert!(CID_LEN <= 20);
+    CID_LEN.to_le_bytes()[0]
+};
+// 2-byte varint length covering everything after the token length byte.
+const LENGTH_FIELD: [u8; 2] = {
+    let remaining = INITIAL_DATAGRAM_LEN - (SCID_OFFSET + CID_LEN + 1) - 2;
+    assert!(remaining < 0x4000);
+    let bytes = (0x4000 | remaining).to_le_bytes();
+    [bytes[1], bytes[0]]
+};
+
+fn connection_id() -> [u8; CID_LEN] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    RandomState::new().hash_one(nanos).to_be_bytes()
+}
+
+pub fn version_probe() -> Vec<u8> {
+    let mut packet = Vec::with_capacity(INITIAL_DATAGRAM_LEN);
+    packet.push(0xc3); // long header, fixed bit, Initial, 4-byte packet number
+    packet.extend_from_slice(&GREASE_VERSION.to_be_bytes());
+    packet.push(CID_LEN_FIELD);
+    packet.extend_from_slice(&connection_id());
+    packet.push(CID_LEN_FIELD);
+    packet.extend_from_slice(&connection_id());
+    packet.push(0x00); // token length
+    packet.extend_from_slice(&LENGTH_FIELD);
+    packet.resize(INITIAL_DATAGRAM_LEN, 0);
+    packet
+}
+
+pub fn parse_version_negotiation(probe: &[u8], response: &[u8]) -> Option<Vec<u32>> {
+    if response.len() < 7 || response[0] & 0x80 == 0 || response[1..5] != [0, 0, 0, 0] {
+        return None;
+    }
+    let dcid_len = usize::from(response[5]);
+    let dcid = response.get(6..6 + dcid_len)?;
+    let our_scid = probe.get(SCID_OFFSET..SCID_OFFSET + CID_LEN)?;
+    if dcid != our_scid {
+        return None;
+    }
+    let scid_len = usize::from(*response.get(6 + dcid_len)?);
+    let versions_start = 7 + dcid_len + scid_len;
+    let versions = response.get(versions_start..)?;
+    let (chunks, _) = versions.as_chunks::<4>();
+    Some(chunks.iter().map(|c| u32::from_be_bytes(*c)).collect())
+}
+
+pub fn version_name(version: u32) -> String {
+    match version {
+        0x0000_0001 => "v1".to_string(),
+        0x6b33_43cf => "v2".to_string(),
+        v if v & 0x0f0f_0f0f == 0x0a0a_0a0a => "grease".to_string(),
+        v if v >> 8 == 0x00ff_0000 => format!("draft-{}", v & 0xff),
+        v if matches!(v >> 24, 0x51 | 0x54) => {
+            String::from_utf8_lossy(&v.to_be_bytes()).into_owned()
+        }
+        v => format!("0x{v:08x}"),
+    }
+}
+
+fn format_quic_reply(
+    ip: &str,
+    asn: &str,
+    port: u16,
+    rtt: Duration,
+    versions: Option<&[u32]>,
+    bytes: usize,
+    minimal: bool,
+) -> String {
+    let show_asn = !minimal || asn != "no lookup";
+    let prefix = if minimal {
+        String::new()
+    } else {
+        format!("{} ", "[MEOWPING]".magenta())
+    };
+    let detail = versions.map_or_else(
+        || format!("bytes={bytes} (not a Version Negotiation packet)"),
+        |versions| {
+            let names = versions
+                .iter()
+                .map(|v| version_name(*v))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("versions={}", names.green())
+        },
+    );
+    let time_colored = color_time(rtt.as_secs_f64() * 1000.0);
+    if show_asn {
+        format!(
+            "{prefix}{} ({}): {} protocol={} port={} {}",
+            ip.green(),
+            asn.green(),
+            time_colored,
+            "QUIC".green(),
+            port.to_string().green(),
+            detail
+        )
+    } else {
+        format!(
+            "{prefix}{}: {} protocol={} port={} {}",
+            ip.green(),
+            time_colored,
+            "QUIC".green(),
+            port.to_string().green(),
+            detail
+        )
+    }
+}
+
+pub fn perform_quic(
+    destination: &str,
+    port: u16,
+    timeout: u64,
+    count: usize,
+    minimal: bool,
+    no_asn: bool,
+) -> Result<(), Box<dyn Error>> {
+    let ip_lookup = resolve_ip(destination, port)?;
+
+    if parse_scoped_ip(destination).is_none() {
+        print_ip_info(destination, &ip_lookup.ip().to_string(), minimal);
+    }
+
+    let asn = fetch_asn(&ip_lookup.ip().to_string(), no_asn, timeout)?;
+    let timeout_dur = Duration::from_millis(timeout);
+
+    let mut times: VecDeque<u128> = VecDeque::new();
+    let mut successes = 0usize;
+
+    for attempt_idx in 0..count {
+        let probe = version_probe();
+        let mut buf = [0u8; 1500];
+        let outcome = udp_exchange(ip_lookup, &probe, timeout_dur, &mut buf);
+        let entry = match outcome {
+            ProbeOutcome::Open { rtt, bytes } => {
+                successes += 1;
+                times.push_back(rtt.as_micros());
+                let versions = parse_version_negotiation(&probe, &buf[..bytes]);
+                format_quic_reply(
+                    &ip_lookup.ip().to_string(),
+                    &asn,
+                    port,
+                    rtt,
+                    versions.as_deref(),
+                    bytes,
+                    minimal,
+                )
+            }
+            other => {
+                times.push_back(0);
+                format_udp_status(ip_lookup.ip(), &asn, port, &other, "QUIC", minimal)
+            }
+        };
+        println!("{entry}");
+
+        if attempt_idx + 1 != count {
+            sleep(Duration::from_secs(1));
+        }
+    }
+
+    print_statistics("QUIC", count, successes, &times);
+    Ok(())
+}
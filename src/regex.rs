@@ -0,0 +1,541 @@
+const MAX_REPEAT: u32 = 1000;
+// Counted repeats are expanded while compiling, so nested ones multiply; cap the result.
+const MAX_PROGRAM: usize = 20_000;
+
+enum Node {
+    Empty,
+    Literal(char),
+    Any,
+    Class(Vec<(char, char)>, bool),
+    Start,
+    End,
+    Concat(Vec<Self>),
+    Alt(Vec<Self>),
+    Repeat(Box<Self>, u32, Option<u32>, bool),
+}
+
+#[derive(Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Vec<(char, char)>, bool),
+    Start,
+    End,
+    Split(usize, usize),
+    Jmp(usize),
+    Match,
+}
+
+// Per-search scratch space: `seen[pc] == generation` marks a pc already added at this position.
+struct Visited {
+    seen: Vec<usize>,
+    generation: usize,
+    stack: Vec<usize>,
+}
+
+pub struct Regex {
+    prog: Vec<Inst>,
+    case_insensitive: bool,
+}
+
+struct ParseState<'a> {
+    chars: &'a [char],
+    pos: usize,
+}
+
+impl ParseState<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            Node::Alt(branches)
+        })
+    }
+
+    fn concat(&mut self) -> Result<Node, String> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            items.push(self.quantified(atom)?);
+        }
+        Ok(match items.len() {
+            0 => Node::Empty,
+            1 => items.remove(0),
+            _ => Node::Concat(items),
+        })
+    }
+
+    fn quantified(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some('*') => self.single(0, None),
+            Some('+') => self.single(1, None),
+            Some('?') => self.single(0, Some(1)),
+            Some('{') => match self.bounds()? {
+                Some(bounds) => bounds,
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        if matches!(atom, Node::Start | Node::End) {
+            return Err("Nothing to repeat".to_string());
+        }
+        let greedy = !self.eat('?');
+        Ok(Node::Repeat(Box::new(atom), min, max, greedy))
+    }
+
+    const fn single(&mut self, min: u32, max: Option<u32>) -> (u32, Option<u32>) {
+        self.pos += 1;
+        (min, max)
+    }
+
+    fn bounds(&mut self) -> Result<Option<(u32, Option<u32>)>, String> {
+        let rest: String = self.chars[self.pos..].iter().collect();
+        let Some(close) = rest.find('}') else {
+            return Ok(None);
+        };
+        let inner = &rest[1..close];
+        let parse = |s: &str| s.parse::<u32>().ok().filter(|n| *n <= MAX_REPEAT);
+        let bounds = match inner.split_once(',') {
+            None => parse(inner).map(|n| (n, Some(n))),
+            Some((lo, "")) => parse(lo).map(|n| (n, None)),
+            Some((lo, hi)) => parse(lo).zip(parse(hi)).map(|(lo, hi)| (lo, Some(hi))),
+        };
+        let Some(bounds) = bounds else {
+            return Ok(None);
+        };
+        if bounds.1.is_some_and(|hi| hi < bounds.0) {
+            return Err(format!("Invalid repetition {{{inner}}}"));
+        }
+        self.pos += rest[..=close].chars().count();
+        Ok(Some(bounds))
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let c = self.next().ok_or("Unexpected end of pattern")?;
+        match c {
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Start),
+            '$' => Ok(Node::End),
+            '(' => {
+                if self.chars[self.pos..].starts_with(&['?', ':']) {
+                    self.pos += 2;
+                }
+                let inner = self.alternation()?;
+                if !self.eat(')') {
+                    return Err("Missing closing ')'".to_string());
+                }
+                Ok(inner)
+            }
+            '[' => self.class(),
+            '\\' => self.escape(),
+            '*' | '+' | '?' => Err(format!("Nothing to repeat before '{c}'")),
+            ')' => Err("Unmatched ')'".to_string()),
+            c => Ok(Node::Literal(c)),
+        }
+    }
+
+    fn escape(&mut self) -> Result<Node, String> {
+        let c = self.next().ok_or("Trailing backslash in pattern")?;
+        match shorthand_class(c) {
+            Some((ranges, negated)) => Ok(Node::Class(ranges, negated)),
+            None => escaped_char(c).map(Node::Literal),
+        }
+    }
+
+    fn class(&mut self) -> Result<Node, String> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.next().ok_or("Missing closing ']'")?;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let lo = if c == '\\' {
+                let e = self.next().ok_or("Trailing backslash in pattern")?;
+                if let Some((shorthand, false)) = shorthand_class(e) {
+                    ranges.extend(shorthand);
+                    continue;
+                }
+                escaped_char(e)?
+            } else {
+                c
+            };
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|c| *c != ']') {
+                self.pos += 1;
+                let mut hi = self.next().ok_or("Missing closing ']'")?;
+                if hi == '\\' {
+                    hi = escaped_char(self.next().ok_or("Trailing backslash in pattern")?)?;
+                }
+                if hi < lo {
+                    return Err(format!("Invalid class range {lo}-{hi}"));
+                }
+                ranges.push((lo, hi));
+            } else {
+                ranges.push((lo, lo));
+            }
+        }
+        Ok(Node::Class(ranges, negated))
+    }
+}
+
+fn escaped_char(c: char) -> Result<char, String> {
+    match c {
+        'n' => Ok('\n'),
+        'r' => Ok('\r'),
+        't' => Ok('\t'),
+        c if c.is_ascii_alphanumeric() => Err(format!("Unsupported escape \\{c}")),
+        c => Ok(c),
+    }
+}
+
+fn shorthand_class(c: char) -> Option<(Vec<(char, char)>, bool)> {
+    let digits = vec![('0', '9')];
+    let word = vec![('0', '9'), ('A', 'Z'), ('a', 'z'), ('_', '_')];
+    let space = vec![(' ', ' '), ('\t', '\r')];
+    match c {
+        'd' => Some((digits, false)),
+        'D' => Some((digits, true)),
+        'w' => Some((word, false)),
+        'W' => Some((word, true)),
+        's' => Some((space, false)),
+        'S' => Some((space, true)),
+        _ => None,
+    }
+}
+
+fn compile(node: &Node, prog: &mut Vec<Inst>) -> Result<(), String> {
+    if prog.len() > MAX_PROGRAM {
+        return Err(format!(
+            "Pattern is too large (over {MAX_PROGRAM} instructions after expanding repeats)"
+        ));
+    }
+    match node {
+        Node::Empty => {}
+        Node::Literal(c) => prog.push(Inst::Char(*c)),
+        Node::Any => prog.push(Inst::Any),
+        Node::Class(ranges, negated) => prog.push(Inst::Class(ranges.clone(), *negated)),
+        Node::Start => prog.push(Inst::Start),
+        Node::End => prog.push(Inst::End),
+        Node::Concat(items) => {
+            for item in items {
+                compile(item, prog)?;
+            }
+        }
+        Node::Alt(branches) => {
+            let mut jumps = Vec::new();
+            for (i, branch) in branches.iter().enumerate() {
+                if i + 1 < branches.len() {
+                    let split = prog.len();
+                    prog.push(Inst::Split(split + 1, 0));
+                    compile(branch, prog)?;
+                    jumps.push(prog.len());
+                    prog.push(Inst::Jmp(0));
+                    let next = prog.len();
+                    prog[split] = Inst::Split(split + 1, next);
+                } else {
+                    compile(branch, prog)?;
+                }
+            }
+            let end = prog.len();
+            for jump in jumps {
+                prog[jump] = Inst::Jmp(end);
+            }
+        }
+        Node::Repeat(inner, min, max, greedy) => {
+            for _ in 0..*min {
+                compile(inner, prog)?;
+            }
+            let split = |body: usize, exit: usize| {
+                if *greedy {
+                    Inst::Split(body, exit)
+                } else {
+                    Inst::Split(exit, body)
+                }
+            };
+            match max {
+                None => {
+                    let start = prog.len();
+                    prog.push(Inst::Match);
+                    compile(inner, prog)?;
+                    prog.push(Inst::Jmp(start));
+                    let exit = prog.len();
+                    prog[start] = split(start + 1, exit);
+                }
+                Some(max) => {
+                    let mut holes = Vec::new();
+                    for _ in *min..*max {
+                        holes.push(prog.len());
+                        prog.push(Inst::Match);
+                        compile(inner, prog)?;
+                    }
+                    let exit = prog.len();
+                    for hole in holes {
+                        prog[hole] = split(hole + 1, exit);
+                    }
+                }
+            }
+        }
+    }
+    if prog.len() > MAX_PROGRAM {
+        return Err(format!(
+            "Pattern is too large (over {MAX_PROGRAM} instructions after expanding repeats)"
+        ));
+    }
+    Ok(())
+}
+
+impl Regex {
+    pub fn with_flags(pattern: &str, case_insensitive: bool) -> Result<Self, String> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut state = ParseState {
+            chars: &chars,
+            pos: 0,
+        };
+        let node = state.alternation()?;
+        if state.pos < chars.len() {
+            return Err("Unmatched ')'".to_string());
+        }
+        let mut prog = Vec::new();
+        compile(&node, &mut prog)?;
+        prog.push(Inst::Match);
+        Ok(Self {
+            prog,
+            case_insensitive,
+        })
+    }
+
+    fn char_matches(&self, inst: &Inst, c: char) -> bool {
+        let test = |c: char| match inst {
+            Inst::Char(expected) => *expected == c,
+            Inst::Any => c != '\n',
+            Inst::Class(ranges, negated) => {
+                ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&c)) != *negated
+            }
+            _ => false,
+        };
+        test(c)
+            || (self.case_insensitive && (c.to_lowercase().any(test) || c.to_uppercase().any(test)))
+    }
+
+    // Follows jumps and splits with an explicit stack, so long programs cannot overflow
+    // the call stack. Pushing the second branch first keeps the first one preferred.
+    fn add_thread(
+        &self,
+        list: &mut Vec<(usize, usize)>,
+        visited: &mut Visited,
+        pc: usize,
+        start: usize,
+        at: usize,
+        len: usize,
+    ) {
+        visited.stack.push(pc);
+        while let Some(pc) = visited.stack.pop() {
+            if visited.seen[pc] == visited.generation {
+                continue;
+            }
+            visited.seen[pc] = visited.generation;
+            match self.prog[pc] {
+                Inst::Jmp(target) => visited.stack.push(target),
+                Inst::Split(first, second) => {
+                    visited.stack.push(second);
+                    visited.stack.push(first);
+                }
+                Inst::Start if at == 0 => visited.stack.push(pc + 1),
+                Inst::End if at == len => visited.stack.push(pc + 1),
+                Inst::Start | Inst::End => {}
+                _ => list.push((pc, start)),
+            }
+        }
+    }
+
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        self.find_at(text, 0)
+    }
+
+    // Leftmost match that starts at or after byte offset `from`, as byte offsets into `text`.
+    // Anchors still refer to the whole text, so repeated searches stay linear overall.
+    pub fn find_at(&self, text: &str, from: usize) -> Option<(usize, usize)> {
+        let mut visited = Visited {
+            seen: vec![0; self.prog.len()],
+            generation: 0,
+            stack: Vec::new(),
+        };
+        let mut current: Vec<(usize, usize)> = Vec::new();
+        let mut next: Vec<(usize, usize)> = Vec::new();
+        let mut found: Option<(usize, usize)> = None;
+
+        let mut at = from;
+        loop {
+            let c = text.get(at..).and_then(|rest| rest.chars().next());
+            visited.generation += 1;
+            next.clear();
+            for &(pc, start) in &current {
+                self.add_thread(&mut next, &mut visited, pc, start, at, text.len());
+            }
+            if found.is_none() {
+                self.add_thread(&mut next, &mut visited, 0, at, at, text.len());
+            }
+
+            current.clear();
+            for &(pc, start) in &next {
+                if matches!(self.prog[pc], Inst::Match) {
+                    found = Some((start, at));
+                    break;
+                }
+                if let Some(c) = c
+                    && self.char_matches(&self.prog[pc], c)
+                {
+                    current.push((pc + 1, start));
+                }
+            }
+            let Some(c) = c else { break };
+            if current.is_empty() && found.is_some() {
+                break;
+            }
+            at += c.len_utf8();
+        }
+        found
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Regex;
+
+    fn find(pattern: &str, text: &str) -> Option<(usize, usize)> {
+        Regex::with_flags(pattern, false).unwrap().find(text)
+    }
+
+    #[test]
+    fn anchors() {
+        assert_eq!(find("^ok", "ok then"), Some((0, 2)));
+        assert_eq!(find("^ok", "not ok"), None);
+        assert_eq!(find("ok$", "not ok"), Some((4, 6)));
+        assert_eq!(find("ok$", "ok then"), None);
+        assert_eq!(find("^$", ""), Some((0, 0)));
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(find("[a-c]+", "xxabcabd"), Some((2, 7)));
+        assert_eq!(find("[^0-9]", "123a"), Some((3, 4)));
+        assert_eq!(find(r"\d+", "build 4711 ok"), Some((6, 10)));
+        assert_eq!(find(r"\w+\s\w+", "-- hello world"), Some((3, 14)));
+        assert_eq!(find(r"[\d.]+", "v1.2.3"), Some((1, 6)));
+        assert_eq!(find("[]a]", "x]"), Some((1, 2)));
+        assert!(Regex::with_flags("[z-a]", false).is_err());
+        assert!(Regex::with_flags("[abc", false).is_err());
+    }
+
+    #[test]
+    fn alternation_and_groups() {
+        assert_eq!(find("cat|dog", "hotdog"), Some((3, 6)));
+        assert_eq!(find("(?:ab|cd)+e", "xxabcdabe"), Some((2, 9)));
+        assert_eq!(find("a(b|)c", "ac"), Some((0, 2)));
+        assert!(Regex::with_flags("(ab", false).is_err());
+        assert!(Regex::with_flags("ab)", false).is_err());
+    }
+
+    #[test]
+    fn counted_repeats() {
+        assert_eq!(find("a{3}", "aaaa"), Some((0, 3)));
+        assert_eq!(find("a{2,3}", "a aaaa"), Some((2, 5)));
+        assert_eq!(find("a{2,}", "aaaaa"), Some((0, 5)));
+        assert_eq!(find("a{2,3}?", "aaaa"), Some((0, 2)));
+        assert_eq!(find("^x{3}$", "xx"), None);
+        assert!(Regex::with_flags("a{3,2}", false).is_err());
+        // Not a valid repeat, so the braces are literal text.
+        assert_eq!(find("a{x}", "a{x}"), Some((0, 4)));
+    }
+
+    #[test]
+    fn greedy_and_lazy() {
+        assert_eq!(find("<.+>", "<a><b>"), Some((0, 6)));
+        assert_eq!(find("<.+?>", "<a><b>"), Some((0, 3)));
+        assert_eq!(find("a.c", "a\nc"), None);
+    }
+
+    #[test]
+    fn empty_matches() {
+        assert_eq!(find("", "abc"), Some((0, 0)));
+        assert_eq!(find("x*", "abc"), Some((0, 0)));
+        assert_eq!(find("b*", "abbb"), Some((0, 0)));
+        assert_eq!(find("()", ""), Some((0, 0)));
+    }
+
+    #[test]
+    fn case_insensitive_and_unicode() {
+        let re = Regex::with_flags("status: ok", true).unwrap();
+        assert!(re.is_match("STATUS: OK"));
+        assert_eq!(find("ü+", "grüüß"), Some((2, 6)));
+    }
+
+    #[test]
+    fn nested_repeats_are_capped() {
+        assert!(Regex::with_flags("(a{1000}){1000}", false).is_err());
+        assert!(Regex::with_flags("((a{100}){100}){100}", false).is_err());
+        assert!(Regex::with_flags("(ab|cd){1000}", false).is_ok());
+        // Deep chains of empty loops must not exhaust the stack.
+        let long = "(?:a?)".repeat(3000);
+        assert!(Regex::with_flags(&long, false).unwrap().is_match("b"));
+    }
+
+    #[test]
+    fn find_at_continues_in_the_same_text() {
+        let re = Regex::with_flags("[0-9]+", false).unwrap();
+        assert_eq!(re.find_at("a1 b22 c333", 2), Some((4, 6)));
+        assert_eq!(re.find_at("a1 b22 c333", 6), Some((8, 11)));
+        assert_eq!(re.find_at("a1 b22 c333", 11), None);
+        // "^" only matches at the start of the text, not at the search offset.
+        let re = Regex::with_flags("^x", false).unwrap();
+        assert_eq!(re.find_at("xxx", 1), None);
+        let re = Regex::with_flags("é$", false).unwrap();
+        assert_eq!(re.find_at("éé", 0), Some((2, 4)));
+        assert_eq!(re.find_at("éé", 2), Some((2, 4)));
+    }
+
+    #[test]
+    fn many_matches_on_a_long_line() {
+        let re = Regex::with_flags("id=[0-9]+", false).unwrap();
+        let line = "id=12345 ".repeat(20_000);
+        let mut pos = 0;
+        let mut count = 0;
+        while let Some((_, end)) = re.find_at(&line, pos) {
+            pos = end;
+            count += 1;
+        }
+        assert_eq!(count, 20_000);
+    }
+}
@@ -1,11 +1,12 @@
 use crate::colors::Colorize;
 use crate::icmp::ping_host_once;
 use crate::output::{color_time, micros_to_ms, print_statistics, print_with_prefix};
+use crate::parser::{scoped_socket_addr, split_zone};
 use crate::tcp::tcp_connect_once;
-use crate::udp::{ProbeOutcome, udp_probe_once};
+use crate::udp::{IcmpReport, ProbeOutcome, udp_probe_once};
 use std::collections::{HashSet, VecDeque};
 use std::fmt::Write as _;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::thread;
 use std::time::Duration;
 
@@ -194,11 +195,13 @@ impl<W: Word> Iterator for SubnetHostIter<W> {
 pub struct Ipv6Subnet {
     network: u128,
     prefix: u8,
+    scope_id: u32,
 }
 
 impl Ipv6Subnet {
     pub fn from_str(input: &str) -> Result<Self, &'static str> {
-        let (ip_part, prefix) = parse_prefix(input, Family::V6)?;
+        let (input, scope_id) = split_zone(input)?;
+        let (ip_part, prefix) = parse_prefix(&input, Family::V6)?;
 
         let ip = ip_part
             .parse::<Ipv6Addr>()
@@ -213,11 +216,28 @@ impl Ipv6Subnet {
         };
         let network = u128::from(ip) & mask;
 
-        Ok(Self { network, prefix })
+        Ok(Self {
+            network,
+            prefix,
+            scope_id,
+        })
     }
 
     pub fn notation(&self) -> String {
-        format!("{}/{}", Ipv6Addr::from(self.network), self.prefix)
+        if self.scope_id == 0 {
+            format!("{}/{}", Ipv6Addr::from(self.network), self.prefix)
+        } else {
+            format!(
+                "{}%{}/{}",
+                Ipv6Addr::from(self.network),
+                self.scope_id,
+                self.prefix
+            )
+        }
+    }
+
+    pub const fn scope_id(&self) -> u32 {
+        self.scope_id
     }
 
     pub fn host_count(&self) -> u128 {
@@ -319,6 +339,7 @@ enum ScanVerdict {
     Open { rtt_us: u128 },
     Down,
     UdpClosed,
+    UdpUnreachable(IcmpReport),
     UdpNoResponse,
 }
 
@@ -352,6 +373,9 @@ impl ScanResult {
             }
             ScanVerdict::Down => self.host.to_string().red(),
             ScanVerdict::UdpClosed => format!("{} closed", self.host.to_string().red()),
+            ScanVerdict::UdpUnreachable(report) => {
+                format!("{} {}", self.host.to_string().red(), report.describe())
+            }
             ScanVerdict::UdpNoResponse => {
                 format!("{} open|filtered", self.host.to_string().orange())
             }
@@ -378,26 +402,33 @@ fn print_chunk_row(results: &[ScanResult], minimal: bool, attempt_idx: usize, at
     print_with_prefix(minimal, &line);
 }
 
-fn probe_chunk(hosts: &[IpAddr], kind: &ProbeKind, seq: &mut u16) -> Vec<ScanResult> {
+fn probe_chunk(
+    hosts: &[IpAddr],
+    scope_id: u32,
+    kind: &ProbeKind,
+    seq: &mut u16,
+) -> Vec<ScanResult> {
     let mut handles = Vec::with_capacity(hosts.len());
     for &host in hosts {
         let verdict = match *kind {
             ProbeKind::Tcp { port, timeout_ms } => thread::spawn(move || {
-                tcp_connect_once(host, port, timeout_ms).map_or(ScanVerdict::Down, |latency| {
-                    ScanVerdict::Open {
+                tcp_connect_once(scoped_socket_addr(host, port, scope_id), timeout_ms).map_or(
+                    ScanVerdict::Down,
+                    |latency| ScanVerdict::Open {
                         rtt_us: latency.as_micros(),
-                    }
-                })
+                    },
+                )
             }),
             ProbeKind::Udp { port, timeout } => {
                 let payload = crate::udp::probe_payload(port);
                 thread::spawn(move || {
-                    let addr = SocketAddr::new(host, port);
+                    let addr = scoped_socket_addr(host, port, scope_id);
                     match udp_probe_once(addr, &payload, timeout) {
                         ProbeOutcome::Open { rtt, .. } => ScanVerdict::Open {
                             rtt_us: rtt.as_micros(),
                         },
                         ProbeOutcome::Closed => ScanVerdict::UdpClosed,
+                        ProbeOutcome::Unreachable(report) => ScanVerdict::UdpUnreachable(report),
                         ProbeOutcome::NoResponse => ScanVerdict::UdpNoResponse,
                     }
                 })
@@ -411,7 +442,8 @@ fn probe_chunk(hosts: &[IpAddr], kind: &ProbeKind, seq: &mut u16) -> Vec<ScanRes
                 let current_seq = *seq;
                 *seq = seq.wrapping_add(1);
                 thread::spawn(move || {
-                    match ping_host_once(host, current_seq, timeout, ttl, ident, &payload) {
+                    match ping_host_once(host, scope_id, current_seq, timeout, ttl, ident, &payload)
+                    {
                         Ok((_bytes, rtt)) => ScanVerdict::Open {
                             rtt_us: rtt.as_micros(),
                         },
@@ -498,6 +530,7 @@ fn print_responsive_minimal(responsive_hosts: &HashSet<IpAddr>, minimal: bool) {
 
 struct ScanConfig {
     notation: String,
+    scope_id: u32,
     host_count: u128,
     too_large: bool,
     kind: ProbeKind,
@@ -543,7 +576,7 @@ fn perform_subnet_scan(hosts: &[IpAddr], cfg: &ScanConfig) {
 
     for attempt_idx in 0..attempts {
         for chunk in hosts.chunks(chunk_size) {
-            let results = probe_chunk(chunk, &cfg.kind, &mut seq);
+            let results = probe_chunk(chunk, cfg.scope_id, &cfg.kind, &mut seq);
             if !cfg.minimal {
                 print_chunk_row(&results, cfg.minimal, attempt_idx + 1, attempts);
             }
@@ -582,6 +615,7 @@ pub fn perform_tcp_subnet_scan(
         &hosts,
         &ScanConfig {
             notation: subnet.notation(),
+            scope_id: 0,
             host_count: subnet.host_count(),
             too_large: false,
             kind: ProbeKind::Tcp { port, timeout_ms },
@@ -605,6 +639,7 @@ pub fn perform_icmp_subnet_scan(
         &hosts,
         &ScanConfig {
             notation: subnet.notation(),
+            scope_id: 0,
             host_count: subnet.host_count(),
             too_large: false,
             kind: ProbeKind::Icmp {
@@ -632,6 +667,7 @@ pub fn perform_tcp_ipv6_subnet_scan(
         &hosts,
         &ScanConfig {
             notation: subnet.notation(),
+            scope_id: subnet.scope_id(),
             host_count,
             too_large: host_count == u128::MAX,
             kind: ProbeKind::Tcp { port, timeout_ms },
@@ -656,6 +692,7 @@ pub fn perform_icmp_ipv6_subnet_scan(
         &hosts,
         &ScanConfig {
             notation: subnet.notation(),
+            scope_id: subnet.scope_id(),
             host_count,
             too_large: host_count == u128::MAX,
             kind: ProbeKind::Icmp {
@@ -682,6 +719,7 @@ pub fn perform_udp_subnet_scan(
         &hosts,
         &ScanConfig {
             notation: subnet.notation(),
+            scope_id: 0,
             host_count: subnet.host_count(),
             too_large: false,
             kind: ProbeKind::Udp {
@@ -707,6 +745,7 @@ pub fn perform_udp_ipv6_subnet_scan(
         &hosts,
         &ScanConfig {
             notation: subnet.notation(),
+            scope_id: subnet.scope_id(),
             host_count,
             too_large: host_count == u128::MAX,
             kind: ProbeKind::Udp {
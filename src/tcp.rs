@@ -1,10 +1,16 @@
 use crate::colors::Colorize;
-use crate::https;
+use crate::https::{self, Credentials};
 use crate::output::{color_time, micros_to_ms, print_statistics};
+use crate::parser::{parse_scoped_ip, scoped_socket_addr};
+use crate::proxy::Proxy;
 use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt;
-use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::OnceLock;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
@@ -20,8 +26,8 @@ impl fmt::Display for MeowpingError {
 impl Error for MeowpingError {}
 
 pub fn resolve_ip(destination: &str, port: u16) -> Result<SocketAddr, Box<dyn Error>> {
-    if let Ok(ip) = destination.parse::<std::net::IpAddr>() {
-        return Ok(SocketAddr::new(ip, port));
+    if let Some((ip, scope_id)) = parse_scoped_ip(destination) {
+        return Ok(scoped_socket_addr(ip, port, scope_id));
     }
     let with_port = if destination.contains(':') {
         format!("[{destination}]:{port}")
@@ -41,10 +47,17 @@ pub fn resolve_ip(destination: &str, port: u16) -> Result<SocketAddr, Box<dyn Er
     Ok(*chosen)
 }
 
+// Credentials for the ipinfo.io lookup (e.g. --bearer-token for a paid API token).
+static ASN_CREDENTIALS: OnceLock<Credentials> = OnceLock::new();
+
+pub fn set_asn_credentials(credentials: Credentials) {
+    let _ = ASN_CREDENTIALS.set(credentials);
+}
+
 const fn is_private_ip(ip_addr: &std::net::IpAddr) -> bool {
     match ip_addr {
-        std::net::IpAddr::V4(ip) => ip.is_private(),
-        std::net::IpAddr::V6(ip) => ip.is_unique_local(),
+        std::net::IpAddr::V4(ip) => ip.is_private() || ip.is_link_local(),
+        std::net::IpAddr::V6(ip) => ip.is_unique_local() || ip.is_unicast_link_local(),
     }
 }
 
@@ -60,8 +73,8 @@ pub fn fetch_asn(ip: &str, no_api: bool, timeout: u64) -> Result<String, Box<dyn
     }
 
     let url = format!("https://ipinfo.io/{ip}/json");
-    let response_text =
-        https::get(&url, timeout).map_err(|e| Box::new(MeowpingError(e.to_string())))?;
+    let response_text = https::get(&url, timeout, ASN_CREDENTIALS.get())
+        .map_err(|e| Box::new(MeowpingError(e.to_string())))?;
     Ok(extract_asn_from_response(&response_text))
 }
 
@@ -129,13 +142,14 @@ pub fn perform_connection(
 }
 
 fn measure_connection_time(ip_lookup: SocketAddr, port: u16, timeout: u64) -> Option<Duration> {
-    tcp_connect_once(ip_lookup.ip(), port, timeout)
+    let mut addr = ip_lookup;
+    addr.set_port(port);
+    tcp_connect_once(addr, timeout)
 }
 
-pub fn tcp_connect_once(ip: IpAddr, port: u16, timeout: u64) -> Option<Duration> {
+pub fn tcp_connect_once(addr: SocketAddr, timeout: u64) -> Option<Duration> {
     let start = Instant::now();
-    let connect_result =
-        TcpStream::connect_timeout(&SocketAddr::new(ip, port), Duration::from_millis(timeout));
+    let connect_result = TcpStream::connect_timeout(&addr, Duration::from_millis(timeout));
 
     if connect_result.is_err() {
         None
@@ -213,7 +227,7 @@ pub fn perform_tcp(
 ) -> Result<(), Box<dyn Error>> {
     let ip_lookup = resolve_ip(destination, port)?;
 
-    if ip_lookup.ip().to_string() != destination {
+    if parse_scoped_ip(destination).is_none() {
         print_ip_info(destination, &ip_lookup.ip().to_string(), minimal);
     }
 
@@ -224,6 +238,135 @@ pub fn perform_tcp(
     Ok(())
 }
 
+fn proxied_connect_once(
+    proxy_addr: SocketAddr,
+    proxy: &Proxy,
+    destination: &str,
+    port: u16,
+    timeout: u64,
+) -> Result<(Duration, Duration), Box<dyn Error>> {
+    let start = Instant::now();
+    let stream = TcpStream::connect_timeout(&proxy_addr, Duration::from_millis(timeout))?;
+    let connect = start.elapsed();
+    proxy.tunnel(stream, destination, port, timeout)?;
+    Ok((connect, start.elapsed()))
+}
+
+pub fn perform_tcp_via_proxy(
+    destination: &str,
+    port: u16,
+    proxy: &Proxy,
+    timeout: u64,
+    count: usize,
+    minimal: bool,
+) -> Result<(), Box<dyn Error>> {
+    let proxy_addr = resolve_ip(&proxy.host, proxy.port)
+        .map_err(|e| format!("Failed to resolve proxy {proxy}: {e}"))?;
+    let prefix = if minimal {
+        String::new()
+    } else {
+        format!("{} ", "[MEOWPING]".magenta())
+    };
+    if !minimal {
+        println!(
+            "{prefix}Probing {} through proxy {}",
+            format!("{destination}:{port}").green(),
+            proxy.to_string().green()
+        );
+    }
+
+    let mut successes = 0;
+    let mut times = VecDeque::new();
+    for attempt_idx in 0..count {
+        match proxied_connect_once(proxy_addr, proxy, destination, port, timeout) {
+            Ok((connect, total)) => {
+                successes += 1;
+                times.push_back(total.as_micros());
+                println!(
+                    "{prefix}{} via proxy: {} (proxy connect={:.2}ms tunnel={:.2}ms) protocol={} port={}",
+                    destination.green(),
+                    color_time(total.as_secs_f64() * 1000.0),
+                    connect.as_secs_f64() * 1000.0,
+                    total.saturating_sub(connect).as_secs_f64() * 1000.0,
+                    "TCP".green(),
+                    port.to_string().green()
+                );
+            }
+            Err(e) => {
+                times.push_back(0);
+                println!(
+                    "{prefix}{} via proxy failed ({}): protocol={} port={}",
+                    destination.red(),
+                    e.to_string().red(),
+                    "TCP".red(),
+                    port.to_string().red()
+                );
+            }
+        }
+        if attempt_idx + 1 != count {
+            sleep(Duration::from_secs(1));
+        }
+    }
+    print_statistics("TCP", count, successes, &times);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+pub fn perform_unix(socket: &Path, count: usize, minimal: bool) -> Result<(), Box<dyn Error>> {
+    let prefix = if minimal {
+        String::new()
+    } else {
+        format!("{} ", "[MEOWPING]".magenta())
+    };
+    let path = socket.display().to_string();
+    if !socket.exists() {
+        return Err(format!("Unix socket '{path}' does not exist").into());
+    }
+    if !minimal {
+        println!("{prefix}Probing Unix socket {}", path.green());
+    }
+
+    let mut successes = 0;
+    let mut times = VecDeque::new();
+    for attempt in 0..count {
+        let start = Instant::now();
+        match UnixStream::connect(socket) {
+            Ok(_) => {
+                let rtt = start.elapsed();
+                successes += 1;
+                times.push_back(rtt.as_micros());
+                println!(
+                    "{prefix}{}: {} protocol={}",
+                    path.green(),
+                    color_time(rtt.as_secs_f64() * 1000.0),
+                    "UNIX".green()
+                );
+            }
+            Err(e) => {
+                times.push_back(0);
+                println!(
+                    "{prefix}{} failed ({}): protocol={}",
+                    path.red(),
+                    e.to_string().red(),
+                    "UNIX".red()
+                );
+            }
+        }
+        if attempt + 1 != count {
+            sleep(Duration::from_secs(1));
+        }
+    }
+    print_statistics("UNIX", count, successes, &times);
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn perform_unix(_socket: &Path, _count: usize, _minimal: bool) -> Result<(), Box<dyn Error>> {
+    Err("Unix domain sockets are not supported on this platform".into())
+}
+
 pub fn perform_tcp_multi_scan(
     hosts: &[String],
     port: u16,
@@ -259,13 +402,13 @@ pub fn perform_tcp_multi_scan(
                         port.to_string().red()
                     );
                     print_with_prefix(minimal, &entry);
-                    results.push((host.clone(), None, "resolve error".to_string()));
+                    results.push((host, None, "resolve error".to_string()));
                     sleep(Duration::from_secs(1));
                     continue;
                 };
                 let asn = fetch_asn(&ip.ip().to_string(), no_asn, timeout_ms)
                     .unwrap_or_else(|_| "?".to_string());
-                let latency = tcp_connect_once(ip.ip(), port, timeout_ms);
+                let latency = tcp_connect_once(ip, timeout_ms);
                 let (latency_micros, entry) = latency.map_or_else(
                     || {
                         (
@@ -295,7 +438,7 @@ pub fn perform_tcp_multi_scan(
                     },
                 );
                 print_with_prefix(minimal, &entry);
-                results.push((host.clone(), latency_micros, asn));
+                results.push((host, latency_micros, asn));
                 sleep(Duration::from_secs(1));
             }
             for (host, latency_micros, _) in &results {
@@ -0,0 +1,259 @@
+use crate::colors::Colorize;
+use crate::http_check::{HttpOptions, redact_url, simplify_error, with_prefix};
+use crate::https::{self, HttpClient, TlsOptions, TlsVersion};
+use crate::output::color_time;
+use crate::parser::Parser;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{Read, Write};
+use std::net::IpAddr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const RECORD_HANDSHAKE: u8 = 22;
+const RECORD_ALERT: u8 = 21;
+const SERVER_HELLO: u8 = 2;
+const ALERT_HANDSHAKE_FAILURE: u8 = 40;
+const ALERT_PROTOCOL_VERSION: u8 = 70;
+
+// ECDHE and plain RSA key exchange with AES-CBC, 3DES and RC4: what TLS 1.0/1.1 servers speak.
+const LEGACY_SUITES: &[u16] = &[
+    0xc014, 0xc013, 0xc00a, 0xc009, 0x0035, 0x002f, 0x000a, 0x0005, 0x0004,
+];
+// x25519, secp256r1, secp384r1
+const GROUPS: &[u16] = &[0x001d, 0x0017, 0x0018];
+
+enum Outcome {
+    // Time to a full handshake, or to the ServerHello for a hand-made ClientHello.
+    Accepted(Duration, &'static str),
+    Rejected(String),
+}
+
+fn describe_rejection(error: &str) -> String {
+    let lower = error.to_ascii_lowercase();
+    if lower.contains("protocol version")
+        || lower.contains("unsupported protocol")
+        || lower.contains("wrong version number")
+    {
+        "protocol version not accepted".to_string()
+    } else if lower.contains("handshake failure") {
+        "handshake failure".to_string()
+    } else {
+        simplify_error(error).to_string()
+    }
+}
+
+const fn wire_version(version: TlsVersion) -> [u8; 2] {
+    match version {
+        TlsVersion::V1_0 => [3, 1],
+        TlsVersion::V1_1 => [3, 2],
+        TlsVersion::V1_2 => [3, 3],
+        TlsVersion::V1_3 => [3, 4],
+    }
+}
+
+fn random_bytes() -> [u8; 32] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    let mut bytes = [0u8; 32];
+    for (i, chunk) in bytes.chunks_mut(8).enumerate() {
+        chunk.copy_from_slice(&RandomState::new().hash_one((nanos, i)).to_be_bytes());
+    }
+    bytes
+}
+
+fn push_u16(out: &mut Vec<u8>, value: usize) {
+    out.extend_from_slice(&u16::try_from(value).unwrap_or(u16::MAX).to_be_bytes());
+}
+
+fn push_extension(out: &mut Vec<u8>, kind: u16, data: &[u8]) {
+    out.extend_from_slice(&kind.to_be_bytes());
+    push_u16(out, data.len());
+    out.extend_from_slice(data);
+}
+
+// A TLS 1.0/1.1 ClientHello that offers exactly one protocol version.
+fn client_hello(version: TlsVersion, server_name: &str) -> Vec<u8> {
+    let mut extensions = Vec::new();
+    if server_name.parse::<IpAddr>().is_err() {
+        let mut sni = vec![0];
+        push_u16(&mut sni, server_name.len());
+        sni.extend_from_slice(server_name.as_bytes());
+        let mut list = Vec::new();
+        push_u16(&mut list, sni.len());
+        list.extend_from_slice(&sni);
+        push_extension(&mut extensions, 0x0000, &list);
+    }
+    let mut groups = Vec::new();
+    push_u16(&mut groups, GROUPS.len() * 2);
+    for group in GROUPS {
+        groups.extend_from_slice(&group.to_be_bytes());
+    }
+    push_extension(&mut extensions, 0x000a, &groups);
+    push_extension(&mut extensions, 0x000b, &[1, 0]); // uncompressed points
+
+    let mut body = wire_version(version).to_vec();
+    body.extend_from_slice(&random_bytes());
+    body.push(0); // no session ID
+    push_u16(&mut body, LEGACY_SUITES.len() * 2);
+    for suite in LEGACY_SUITES {
+        body.extend_from_slice(&suite.to_be_bytes());
+    }
+    body.extend_from_slice(&[1, 0]); // null compression
+    push_u16(&mut body, extensions.len());
+    body.extend_from_slice(&extensions);
+
+    let length = u32::try_from(body.len()).unwrap_or(u32::MAX).to_be_bytes();
+    let mut handshake = vec![1];
+    handshake.extend_from_slice(&length[1..]);
+    handshake.extend_from_slice(&body);
+
+    let mut record = vec![RECORD_HANDSHAKE, 3, 1];
+    push_u16(&mut record, handshake.len());
+    record.extend_from_slice(&handshake);
+    record
+}
+
+fn version_name(wire: [u8; 2]) -> String {
+    TlsVersion::ALL
+        .into_iter()
+        .find(|version| wire_version(*version) == wire)
+        .map_or_else(
+            || format!("version {}.{}", wire[0], wire[1]),
+            |v| v.to_string(),
+        )
+}
+
+// Sends a ClientHello by hand and reads the first record of the answer. Used for versions
+// the local TLS library refuses to offer, such as TLS 1.0 and 1.1 on OpenSSL 3.
+fn probe_raw(client: &HttpClient, url: &str, timeout: u64, version: TlsVersion) -> Outcome {
+    let exchange = || -> Result<Outcome, Box<dyn std::error::Error>> {
+        let (mut stream, server_name) = client.connect_tcp(url, timeout)?;
+        stream.set_read_timeout(Some(Duration::from_millis(timeout)))?;
+        let start = Instant::now();
+        stream.write_all(&client_hello(version, &server_name))?;
+
+        let mut header = [0u8; 5];
+        if stream.read_exact(&mut header).is_err() {
+            return Ok(Outcome::Rejected("connection closed".to_string()));
+        }
+        let elapsed = start.elapsed();
+        let mut fragment = [0u8; 6];
+        let wanted = usize::from(u16::from_be_bytes([header[3], header[4]])).min(fragment.len());
+        stream.read_exact(&mut fragment[..wanted])?;
+        Ok(match (header[0], fragment[0]) {
+            (RECORD_HANDSHAKE, SERVER_HELLO) if wanted == fragment.len() => {
+                let chosen = [fragment[4], fragment[5]];
+                if chosen == wire_version(version) {
+                    Outcome::Accepted(elapsed, "ServerHello after")
+                } else {
+                    Outcome::Rejected(format!("server answered with {}", version_name(chosen)))
+                }
+            }
+            (RECORD_ALERT, _) => Outcome::Rejected(match fragment[1] {
+                ALERT_PROTOCOL_VERSION => "protocol version not accepted".to_string(),
+                ALERT_HANDSHAKE_FAILURE => "handshake failure".to_string(),
+                alert => format!("alert {alert}"),
+            }),
+            _ => Outcome::Rejected("not a TLS response".to_string()),
+        })
+    };
+    exchange().unwrap_or_else(|e| Outcome::Rejected(simplify_error(&e.to_string()).to_string()))
+}
+
+fn scan_version(
+    url: &str,
+    opts: &HttpOptions<'_>,
+    base: &TlsOptions,
+    version: TlsVersion,
+) -> Outcome {
+    // Only protocol support matters here, so certificate problems must not hide it.
+    let tls = TlsOptions {
+        insecure: Some(true),
+        pins: Vec::new(),
+        min_version: Some(version),
+        max_version: Some(version),
+        ..base.clone()
+    };
+    let context = match https::build_tls_context(&tls) {
+        Ok(context) => context,
+        Err(e) => return Outcome::Rejected(e.to_string()),
+    };
+    let client = HttpClient::new(context, opts.routes.clone());
+    match client.handshake(url, opts.timeout) {
+        Ok(timings) => Outcome::Accepted(timings.tls.unwrap_or(timings.total), "handshake"),
+        Err(e) => {
+            let error = e.to_string();
+            if error.contains("no protocols available") && version < TlsVersion::V1_2 {
+                probe_raw(&client, url, opts.timeout, version)
+            } else {
+                Outcome::Rejected(describe_rejection(&error))
+            }
+        }
+    }
+}
+
+pub fn perform_tls_scan(url: &str, opts: &HttpOptions<'_>, base: &TlsOptions) {
+    if !Parser::parse(url).is_ok_and(|url| matches!(url.scheme.as_str(), "https" | "wss")) {
+        println!(
+            "{}",
+            with_prefix(opts.minimal, "A TLS scan needs an https:// or wss:// URL")
+        );
+        return;
+    }
+    println!(
+        "{}",
+        with_prefix(
+            opts.minimal,
+            &format!("TLS protocol scan of {}", redact_url(url))
+        )
+    );
+
+    let mut accepted = Vec::new();
+    for version in TlsVersion::ALL {
+        let line = match scan_version(url, opts, base, version) {
+            Outcome::Accepted(time, label) => {
+                accepted.push(version);
+                let name = version.to_string();
+                let name = if version < TlsVersion::V1_2 {
+                    name.yellow()
+                } else {
+                    name.green()
+                };
+                format!(
+                    "{name} accepted ({label} {})",
+                    color_time(time.as_secs_f64() * 1000.0)
+                )
+            }
+            Outcome::Rejected(reason) => format!("{version} rejected ({reason})"),
+        };
+        println!("    {line}");
+    }
+
+    let list = |versions: &[TlsVersion]| {
+        versions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let summary = if accepted.is_empty() {
+        "No TLS version was accepted".red()
+    } else {
+        format!("Accepted: {}", list(&accepted))
+    };
+    println!("{}", with_prefix(opts.minimal, &summary));
+    let legacy: Vec<TlsVersion> = accepted
+        .iter()
+        .copied()
+        .filter(|version| *version < TlsVersion::V1_2)
+        .collect();
+    if !legacy.is_empty() {
+        println!(
+            "{}",
+            with_prefix(
+                opts.minimal,
+                &format!("Legacy {} still enabled", list(&legacy)).yellow()
+            )
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::colors::Colorize;
 use crate::output::{color_time, micros_to_ms, print_statistics, print_with_prefix};
+use crate::parser::parse_scoped_ip;
 use crate::tcp::{fetch_asn, resolve_ip};
 use std::collections::{HashSet, VecDeque};
 use std::io::ErrorKind;
@@ -29,18 +30,238 @@ const NTP_REQUEST: [u8; 48] = {
 pub enum ProbeOutcome {
     Open { rtt: Duration, bytes: usize },
     Closed,
+    Unreachable(IcmpReport),
     NoResponse,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct IcmpReport {
+    pub v6: bool,
+    pub icmp_type: u8,
+    pub code: u8,
+    pub reporter: Option<IpAddr>,
+}
+
+impl IcmpReport {
+    const fn is_port_unreachable(&self) -> bool {
+        if self.v6 {
+            self.icmp_type == 1 && self.code == 4
+        } else {
+            self.icmp_type == 3 && self.code == 3
+        }
+    }
+
+    pub const fn is_filtered(&self) -> bool {
+        if self.v6 {
+            self.icmp_type == 1 && matches!(self.code, 1 | 5 | 6)
+        } else {
+            self.icmp_type == 3 && matches!(self.code, 9 | 10 | 13)
+        }
+    }
+
+    pub const fn reason(&self) -> &'static str {
+        if self.v6 {
+            match (self.icmp_type, self.code) {
+                (1, 0) => "no route",
+                (1, 1) => "admin prohibited",
+                (1, 2) => "beyond scope",
+                (1, 3) => "address unreachable",
+                (1, 4) => "port unreachable",
+                (1, 5) => "source policy",
+                (1, 6) => "reject route",
+                (2, _) => "packet too big",
+                (3, _) => "hop limit exceeded",
+                _ => "unreachable",
+            }
+        } else {
+            match (self.icmp_type, self.code) {
+                (3, 0) => "net unreachable",
+                (3, 1) => "host unreachable",
+                (3, 2) => "protocol unreachable",
+                (3, 3) => "port unreachable",
+                (3, 4) => "fragmentation needed",
+                (3, 6 | 7) => "destination unknown",
+                (3, 9 | 10 | 13) => "admin prohibited",
+                (11, _) => "TTL exceeded",
+                _ => "unreachable",
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let state = if self.is_filtered() {
+            "filtered"
+        } else {
+            "unreachable"
+        };
+        let proto = if self.v6 { "ICMPv6" } else { "ICMP" };
+        let from = self
+            .reporter
+            .map_or_else(String::new, |ip| format!(" from {ip}"));
+        format!(
+            "{state} ({}, {proto} {}/{}{from})",
+            self.reason(),
+            self.icmp_type,
+            self.code
+        )
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod errqueue {
+    use super::IcmpReport;
+    use std::mem;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, UdpSocket};
+    use std::os::fd::AsRawFd;
+
+    pub fn enable(sock: &UdpSocket, v6: bool) -> bool {
+        let (level, name) = if v6 {
+            (libc::IPPROTO_IPV6, libc::IPV6_RECVERR)
+        } else {
+            (libc::IPPROTO_IP, libc::IP_RECVERR)
+        };
+        let on: libc::c_int = 1;
+        let len = libc::socklen_t::try_from(mem::size_of::<libc::c_int>()).unwrap_or(0);
+        let ret = unsafe {
+            libc::setsockopt(
+                sock.as_raw_fd(),
+                level,
+                name,
+                (&raw const on).cast::<libc::c_void>(),
+                len,
+            )
+        };
+        ret == 0
+    }
+
+    fn read_offender(storage: &libc::sockaddr_storage) -> Option<IpAddr> {
+        let addr = std::ptr::from_ref(storage);
+        match libc::c_int::from(storage.ss_family) {
+            libc::AF_INET => {
+                let sin = unsafe { &*addr.cast::<libc::sockaddr_in>() };
+                Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(
+                    sin.sin_addr.s_addr,
+                ))))
+            }
+            libc::AF_INET6 => {
+                let sin6 = unsafe { &*addr.cast::<libc::sockaddr_in6>() };
+                Some(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn read(sock: &UdpSocket) -> Option<IcmpReport> {
+        let mut data = [0u8; 64];
+        let mut control = [0u8; 512];
+        let mut name: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut iov = libc::iovec {
+            iov_base: data.as_mut_ptr().cast::<libc::c_void>(),
+            iov_len: data.len(),
+        };
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_name = (&raw mut name).cast::<libc::c_void>();
+        msg.msg_namelen =
+            libc::socklen_t::try_from(mem::size_of::<libc::sockaddr_storage>()).unwrap_or(0);
+        msg.msg_iov = &raw mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast::<libc::c_void>();
+        msg.msg_controllen = control.len();
+
+        let ret = unsafe {
+            libc::recvmsg(
+                sock.as_raw_fd(),
+                &raw mut msg,
+                libc::MSG_ERRQUEUE | libc::MSG_DONTWAIT,
+            )
+        };
+        if ret < 0 {
+            return None;
+        }
+
+        let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(&raw const msg) };
+        while !cmsg.is_null() {
+            let hdr = unsafe { std::ptr::read_unaligned(cmsg) };
+            let is_recverr = (hdr.cmsg_level == libc::SOL_IP && hdr.cmsg_type == libc::IP_RECVERR)
+                || (hdr.cmsg_level == libc::SOL_IPV6 && hdr.cmsg_type == libc::IPV6_RECVERR);
+            if is_recverr {
+                let data_ptr = unsafe { libc::CMSG_DATA(cmsg) };
+                let ee =
+                    unsafe { std::ptr::read_unaligned(data_ptr.cast::<libc::sock_extended_err>()) };
+                let v6 = match ee.ee_origin {
+                    libc::SO_EE_ORIGIN_ICMP => false,
+                    libc::SO_EE_ORIGIN_ICMP6 => true,
+                    _ => return None,
+                };
+                // The offender address follows the error in the control message, with no
+                // alignment guarantee; copy it into an aligned sockaddr_storage first.
+                let data_len = hdr
+                    .cmsg_len
+                    .saturating_sub(usize::try_from(unsafe { libc::CMSG_LEN(0) }).unwrap_or(0));
+                let offender_len = data_len
+                    .saturating_sub(mem::size_of::<libc::sock_extended_err>())
+                    .min(mem::size_of::<libc::sockaddr_storage>());
+                let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        data_ptr.add(mem::size_of::<libc::sock_extended_err>()),
+                        (&raw mut storage).cast::<u8>(),
+                        offender_len,
+                    );
+                }
+                let offender = read_offender(&storage);
+                return Some(IcmpReport {
+                    v6,
+                    icmp_type: ee.ee_type,
+                    code: ee.ee_code,
+                    reporter: offender,
+                });
+            }
+            cmsg = unsafe { libc::CMSG_NXTHDR(&raw const msg, cmsg) };
+        }
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn classify_error(sock: &UdpSocket, kind: ErrorKind) -> ProbeOutcome {
+    match errqueue::read(sock) {
+        Some(report) if report.is_port_unreachable() => ProbeOutcome::Closed,
+        Some(report) => ProbeOutcome::Unreachable(report),
+        None if kind == ErrorKind::ConnectionRefused => ProbeOutcome::Closed,
+        None => ProbeOutcome::NoResponse,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn classify_error(_sock: &UdpSocket, kind: ErrorKind) -> ProbeOutcome {
+    if kind == ErrorKind::ConnectionRefused {
+        ProbeOutcome::Closed
+    } else {
+        ProbeOutcome::NoResponse
+    }
+}
+
 pub fn probe_payload(port: u16) -> Vec<u8> {
     match port {
         53 => DNS_QUERY.to_vec(),
         123 => NTP_REQUEST.to_vec(),
+        443 => crate::quic::version_probe(),
         _ => vec![0x00],
     }
 }
 
 pub fn udp_probe_once(addr: SocketAddr, payload: &[u8], timeout: Duration) -> ProbeOutcome {
+    let mut buf = [0u8; 1500];
+    udp_exchange(addr, payload, timeout, &mut buf)
+}
+
+pub fn udp_exchange(
+    addr: SocketAddr,
+    payload: &[u8],
+    timeout: Duration,
+    buf: &mut [u8],
+) -> ProbeOutcome {
     let bind_addr = if addr.is_ipv4() {
         "0.0.0.0:0"
     } else {
@@ -55,31 +276,32 @@ pub fn udp_probe_once(addr: SocketAddr, payload: &[u8], timeout: Duration) -> Pr
     if sock.set_read_timeout(Some(timeout)).is_err() {
         return ProbeOutcome::NoResponse;
     }
+    #[cfg(target_os = "linux")]
+    let _ = errqueue::enable(&sock, addr.is_ipv6());
 
     let start = Instant::now();
     if let Err(e) = sock.send(payload) {
-        if e.kind() == ErrorKind::ConnectionRefused {
-            return ProbeOutcome::Closed;
-        }
-        return ProbeOutcome::NoResponse;
+        return classify_error(&sock, e.kind());
     }
 
-    let mut buf = [0u8; 1500];
-    match sock.recv(&mut buf) {
+    match sock.recv(buf) {
         Ok(n) => ProbeOutcome::Open {
             rtt: start.elapsed(),
             bytes: n,
         },
-        Err(e) if e.kind() == ErrorKind::ConnectionRefused => ProbeOutcome::Closed,
-        Err(_) => ProbeOutcome::NoResponse,
+        Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+            ProbeOutcome::NoResponse
+        }
+        Err(e) => classify_error(&sock, e.kind()),
     }
 }
 
-fn format_udp_status(
+pub fn format_udp_status(
     ip: IpAddr,
     asn: &str,
     port: u16,
     outcome: &ProbeOutcome,
+    proto: &str,
     minimal: bool,
 ) -> String {
     let show_asn = !minimal || asn != "no lookup";
@@ -88,7 +310,6 @@ fn format_udp_status(
     } else {
         format!("{} ", "[MEOWPING]".magenta())
     };
-    let proto = "UDP";
 
     match outcome {
         ProbeOutcome::Open { rtt, bytes } => {
@@ -134,6 +355,27 @@ fn format_udp_status(
             };
             format!("{prefix}{body}")
         }
+        ProbeOutcome::Unreachable(report) => {
+            let body = if show_asn {
+                format!(
+                    "{} {} ({}): protocol={} port={}",
+                    ip.to_string().red(),
+                    report.describe(),
+                    asn.red(),
+                    proto.red(),
+                    port.to_string().red()
+                )
+            } else {
+                format!(
+                    "{} {}: protocol={} port={}",
+                    ip.to_string().red(),
+                    report.describe(),
+                    proto.red(),
+                    port.to_string().red()
+                )
+            };
+            format!("{prefix}{body}")
+        }
         ProbeOutcome::NoResponse => {
             let body = if show_asn {
                 format!(
@@ -166,7 +408,7 @@ pub fn perform_udp(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let ip_lookup = resolve_ip(destination, port)?;
 
-    if ip_lookup.ip().to_string() != destination {
+    if parse_scoped_ip(destination).is_none() {
         crate::tcp::print_ip_info(destination, &ip_lookup.ip().to_string(), minimal);
     }
 
@@ -180,7 +422,7 @@ pub fn perform_udp(
     for attempt_idx in 0..count {
         let outcome = udp_probe_once(ip_lookup, &payload, timeout_dur);
         let is_open = matches!(outcome, ProbeOutcome::Open { .. });
-        let entry = format_udp_status(ip_lookup.ip(), &asn, port, &outcome, minimal);
+        let entry = format_udp_status(ip_lookup.ip(), &asn, port, &outcome, "UDP", minimal);
         println!("{entry}");
 
         if is_open {
@@ -231,6 +473,17 @@ fn udp_multi_entry(
             );
             (None, entry)
         }
+        ProbeOutcome::Unreachable(report) => {
+            let entry = format!(
+                "  {} {} ({}): protocol={} port={}",
+                host.red(),
+                report.describe(),
+                asn.red(),
+                "UDP".red(),
+                port.to_string().red()
+            );
+            (None, entry)
+        }
         ProbeOutcome::NoResponse => {
             let entry = format!(
                 "  {} no response (open|filtered) ({}): protocol={} port={}",
@@ -278,7 +531,7 @@ pub fn perform_udp_multi_scan(
                         port.to_string().orange()
                     );
                     print_with_prefix(minimal, &entry);
-                    results.push((host.clone(), None));
+                    results.push((host, None));
                     sleep(Duration::from_secs(1));
                     continue;
                 };
@@ -287,7 +540,7 @@ pub fn perform_udp_multi_scan(
                 let outcome = udp_probe_once(ip, &payload, timeout_dur);
                 let (latency_micros, entry) = udp_multi_entry(&host, &asn, &outcome, port);
                 print_with_prefix(minimal, &entry);
-                results.push((host.clone(), latency_micros));
+                results.push((host, latency_micros));
                 sleep(Duration::from_secs(1));
             }
             for (host, latency_micros) in &results {
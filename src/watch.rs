@@ -0,0 +1,172 @@
+use crate::colors::Colorize;
+use crate::digest;
+use crate::regex::Regex;
+use std::fmt::Write;
+
+const MAX_DIFF_LINES: usize = 12;
+const MAX_LINE_CHARS: usize = 160;
+// Above this many line pairs the diff falls back to one changed block.
+const MAX_DIFF_CELLS: usize = 1_000_000;
+
+pub struct WatchOptions {
+    pub ignore: Vec<Regex>,
+    pub diff: bool,
+}
+
+pub struct ContentWatch<'a> {
+    opts: &'a WatchOptions,
+    last: Option<([u8; 32], Vec<String>)>,
+}
+
+pub enum ContentChange {
+    First(String),
+    Same(String),
+    Changed {
+        from: String,
+        to: String,
+        diff: Vec<String>,
+    },
+}
+
+fn short_hash(hash: &[u8; 32]) -> String {
+    hash[..6].iter().fold(String::new(), |mut out, b| {
+        let _ = write!(out, "{b:02x}");
+        out
+    })
+}
+
+// Removes every match of the patterns from one line.
+fn strip_ignored(line: &str, ignore: &[Regex]) -> String {
+    let mut line = line.to_string();
+    for regex in ignore {
+        let mut out = String::with_capacity(line.len());
+        let mut pos = 0;
+        while let Some((start, end)) = regex.find_at(&line, pos) {
+            out.push_str(&line[pos..start]);
+            if end == start {
+                // Empty match: keep one character and search on.
+                let Some(c) = line[start..].chars().next() else {
+                    pos = line.len();
+                    break;
+                };
+                out.push(c);
+                pos = start + c.len_utf8();
+            } else {
+                pos = end;
+            }
+        }
+        out.push_str(&line[pos..]);
+        line = out;
+    }
+    line
+}
+
+fn shorten(line: &str) -> String {
+    if line.chars().count() > MAX_LINE_CHARS {
+        let cut: String = line.chars().take(MAX_LINE_CHARS).collect();
+        format!("{cut}...")
+    } else {
+        line.to_string()
+    }
+}
+
+// Line diff of the region between the common prefix and suffix, via LCS.
+fn diff_lines(old: &[String], new: &[String]) -> Vec<(char, String)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .map(|l| ('-', l.clone()))
+            .chain(new.iter().map(|l| ('+', l.clone())))
+            .collect();
+    }
+    let width = new.len() + 1;
+    let mut lcs = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i * width + j] = if old[i] == new[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len()
+            && (j == new.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            out.push(('-', old[i].clone()));
+            i += 1;
+        } else {
+            out.push(('+', new[j].clone()));
+            j += 1;
+        }
+    }
+    out
+}
+
+impl<'a> ContentWatch<'a> {
+    pub const fn new(opts: &'a WatchOptions) -> Self {
+        Self { opts, last: None }
+    }
+
+    pub fn observe(&mut self, body: &[u8]) -> ContentChange {
+        let text = String::from_utf8_lossy(body);
+        let lines: Vec<String> = text
+            .lines()
+            .map(|line| strip_ignored(line, &self.opts.ignore))
+            .collect();
+        let hash = digest::sha256(lines.join("\n").as_bytes());
+
+        let change = match &self.last {
+            None => ContentChange::First(short_hash(&hash)),
+            Some((last, _)) if *last == hash => ContentChange::Same(short_hash(&hash)),
+            Some((last, last_lines)) => {
+                let changes = if self.opts.diff {
+                    diff_lines(last_lines, &lines)
+                } else {
+                    Vec::new()
+                };
+                let mut diff: Vec<String> = changes
+                    .iter()
+                    .take(MAX_DIFF_LINES)
+                    .map(|(sign, line)| {
+                        let line = format!("{sign} {}", shorten(line));
+                        if *sign == '+' {
+                            line.green()
+                        } else {
+                            line.red()
+                        }
+                    })
+                    .collect();
+                if changes.len() > MAX_DIFF_LINES {
+                    diff.push(format!(
+                        "... {} more changed line(s)",
+                        changes.len() - MAX_DIFF_LINES
+                    ));
+                }
+                ContentChange::Changed {
+                    from: short_hash(last),
+                    to: short_hash(&hash),
+                    diff,
+                }
+            }
+        };
+        self.last = Some((hash, lines));
+        change
+    }
+}
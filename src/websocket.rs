@@ -0,0 +1,270 @@
+use crate::digest;
+use crate::encoding;
+use crate::http_check::{HttpOptions, format_timings, print_exchange, redact_url, with_prefix};
+use crate::https::{self, HttpClient, HttpReply, RequestSpec, Upgraded};
+use crate::output::{color_time, print_statistics};
+use std::collections::VecDeque;
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::hash::BuildHasher;
+use std::io::{Read, Write};
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// RFC 6455 section 1.3: appended to Sec-WebSocket-Key before hashing.
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xa;
+
+// Data frames that arrive while waiting for a pong are skipped, up to this size.
+const MAX_FRAME: u64 = 16 * 1024 * 1024;
+
+fn random_bytes() -> [u8; 8] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    RandomState::new().hash_one(nanos).to_be_bytes()
+}
+
+fn handshake_key() -> String {
+    let mut key = [0u8; 16];
+    key[..8].copy_from_slice(&random_bytes());
+    key[8..].copy_from_slice(&random_bytes());
+    encoding::base64_encode(&key)
+}
+
+fn accept_value(key: &str) -> String {
+    encoding::base64_encode(&digest::sha1(format!("{key}{ACCEPT_GUID}").as_bytes()))
+}
+
+fn has_token(reply: &HttpReply, name: &str, token: &str) -> bool {
+    reply.header(name).is_some_and(|value| {
+        value
+            .split(',')
+            .any(|t| t.trim().eq_ignore_ascii_case(token))
+    })
+}
+
+fn verify_handshake(reply: &HttpReply, key: &str) -> Result<(), String> {
+    match reply.status {
+        101 => {}
+        426 => {
+            return Err(format!(
+                "Server refused the WebSocket upgrade (426 Upgrade Required{})",
+                reply
+                    .header("Sec-WebSocket-Version")
+                    .map_or_else(String::new, |v| format!(", supports version {v}"))
+            ));
+        }
+        status => {
+            return Err(format!(
+                "Server did not switch to WebSocket: HTTP status {status}"
+            ));
+        }
+    }
+    if !has_token(reply, "Upgrade", "websocket") {
+        return Err("Server answered 101 without 'Upgrade: websocket'".to_string());
+    }
+    if !has_token(reply, "Connection", "upgrade") {
+        return Err("Server answered 101 without 'Connection: Upgrade'".to_string());
+    }
+    let expected = accept_value(key);
+    match reply.header("Sec-WebSocket-Accept") {
+        Some(accept) if accept.trim() == expected => Ok(()),
+        Some(accept) => Err(format!(
+            "Invalid Sec-WebSocket-Accept '{}' (expected '{expected}')",
+            accept.trim()
+        )),
+        None => Err("Server answered 101 without Sec-WebSocket-Accept".to_string()),
+    }
+}
+
+// Client frames are always masked (RFC 6455 section 5.3).
+fn write_frame(stream: &mut Upgraded, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mask = random_bytes();
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..126 => frame.push(0x80 | u8::try_from(len).unwrap_or_default()),
+        len @ 126..65_536 => {
+            frame.push(0xfe); // masked, 16-bit length follows
+            frame.extend_from_slice(&u16::try_from(len).unwrap_or_default().to_be_bytes());
+        }
+        len => {
+            frame.push(0xff); // masked, 64-bit length follows
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&mask[..4]);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    stream.write_all(&frame)
+}
+
+fn read_frame(stream: &mut Upgraded) -> Result<(u8, Vec<u8>), Box<dyn Error>> {
+    let mut head = [0u8; 2];
+    stream.read_exact(&mut head)?;
+    let opcode = head[0] & 0x0f;
+    let len = match head[1] & 0x7f {
+        126 => {
+            let mut ext = [0u8; 2];
+            stream.read_exact(&mut ext)?;
+            u64::from(u16::from_be_bytes(ext))
+        }
+        127 => {
+            let mut ext = [0u8; 8];
+            stream.read_exact(&mut ext)?;
+            u64::from_be_bytes(ext)
+        }
+        len => u64::from(len),
+    };
+    if len > MAX_FRAME {
+        return Err(format!("WebSocket frame of {len} bytes is too large").into());
+    }
+    let mut mask = [0u8; 4];
+    if head[1] & 0x80 != 0 {
+        stream.read_exact(&mut mask)?;
+    }
+    let mut payload = Vec::new();
+    stream.take(len).read_to_end(&mut payload)?;
+    if payload.len() as u64 != len {
+        return Err("Connection closed in the middle of a WebSocket frame".into());
+    }
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok((opcode, payload))
+}
+
+fn ping(stream: &mut Upgraded, seq: usize) -> Result<Duration, Box<dyn Error>> {
+    let payload = format!("meowping {seq}").into_bytes();
+    let start = Instant::now();
+    write_frame(stream, OP_PING, &payload)?;
+    loop {
+        let (opcode, data) = read_frame(stream).map_err(|e| {
+            let timed_out = e.downcast_ref::<std::io::Error>().is_some_and(|e| {
+                matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                )
+            });
+            if timed_out {
+                format!("No pong for ping seq={seq} before the timeout").into()
+            } else {
+                e
+            }
+        })?;
+        match opcode {
+            OP_PONG if data == payload => return Ok(start.elapsed()),
+            OP_PING => write_frame(stream, OP_PONG, &data)?,
+            OP_CLOSE => {
+                let code = data.get(..2).map(|c| u16::from_be_bytes([c[0], c[1]]));
+                let reason = String::from_utf8_lossy(data.get(2..).unwrap_or_default());
+                return Err(match code {
+                    Some(code) if reason.is_empty() => {
+                        format!("Server closed the WebSocket (code {code})")
+                    }
+                    Some(code) => format!("Server closed the WebSocket (code {code}: {reason})"),
+                    None => "Server closed the WebSocket".to_string(),
+                }
+                .into());
+            }
+            // Unsolicited pongs, messages and continuation frames are not ours.
+            OP_PONG | OP_TEXT | OP_BINARY | 0x0 => {}
+            other => return Err(format!("Unexpected WebSocket opcode {other:#x}").into()),
+        }
+    }
+}
+
+fn connect(
+    url: &str,
+    opts: &HttpOptions<'_>,
+    client: &HttpClient,
+) -> Result<Upgraded, Box<dyn Error>> {
+    let key = handshake_key();
+    let headers = https::merge_headers(
+        opts.headers,
+        &[
+            "Upgrade: websocket".to_string(),
+            "Connection: Upgrade".to_string(),
+            format!("Sec-WebSocket-Key: {key}"),
+            "Sec-WebSocket-Version: 13".to_string(),
+        ],
+    );
+    let spec = RequestSpec {
+        method: "GET",
+        headers: &headers,
+        body: None,
+        compressed: false,
+        auth: opts.auth,
+    };
+    let (reply, stream) = client.upgrade(url, opts.timeout, &spec)?;
+    if opts.verbose {
+        print_exchange(&reply);
+    }
+    verify_handshake(&reply, &key)?;
+    let stream = stream.ok_or("Server did not switch protocols")?;
+
+    let mut message = format!(
+        "{} upgraded to WebSocket {}",
+        redact_url(url),
+        format_timings(&reply.timings)
+    );
+    if let Some(via) = &reply.via {
+        let _ = write!(message, " via {via}");
+    }
+    if opts.verbose
+        && let Some(peer) = &reply.peer
+    {
+        let _ = write!(message, " {}", peer.describe());
+    }
+    println!("{}", with_prefix(opts.minimal, &message));
+    Ok(stream)
+}
+
+pub fn perform_websocket_check(url: &str, opts: &HttpOptions<'_>) {
+    let mut times: VecDeque<u128> = VecDeque::new();
+    let mut successes = 0usize;
+    let client = HttpClient::new(opts.tls.clone(), opts.routes.clone());
+    let mut stream: Option<Upgraded> = None;
+
+    for i in 0..opts.count {
+        let attempt = stream
+            .take()
+            .map_or_else(|| connect(url, opts, &client), Ok)
+            .and_then(|mut open| ping(&mut open, i).map(|rtt| (open, rtt)));
+        match attempt {
+            Ok((open, rtt)) => {
+                successes += 1;
+                times.push_back(rtt.as_micros());
+                let ms = rtt.as_secs_f64() * 1000.0;
+                println!(
+                    "{}",
+                    with_prefix(
+                        opts.minimal,
+                        &format!("{} pong seq={i} time={}", redact_url(url), color_time(ms))
+                    )
+                );
+                stream = Some(open);
+            }
+            Err(e) => {
+                times.push_back(0);
+                println!(
+                    "{}",
+                    with_prefix(opts.minimal, &format!("{} failed: {e}", redact_url(url)))
+                );
+            }
+        }
+        if i < opts.count - 1 {
+            sleep(Duration::from_secs(1));
+        }
+    }
+
+    if let Some(mut open) = stream {
+        let _ = write_frame(&mut open, OP_CLOSE, &1000u16.to_be_bytes());
+    }
+    print_statistics("WebSocket", opts.count, successes, &times);
+}
@@ -0,0 +1,132 @@
+// Reads one DER TLV at `pos`; returns (tag, content start, end of element).
+fn read_tlv(data: &[u8], pos: usize) -> Option<(u8, usize, usize)> {
+    let tag = *data.get(pos)?;
+    let first = *data.get(pos + 1)?;
+    let (len, header) = if first & 0x80 == 0 {
+        (usize::from(first), 2)
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let bytes = data.get(pos + 2..pos + 2 + count)?;
+        let len = bytes
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | usize::from(*b));
+        (len, 2 + count)
+    };
+    let start = pos + header;
+    let end = start.checked_add(len)?;
+    (end <= data.len()).then_some((tag, start, end))
+}
+
+// Returns the offset of the TBSCertificate field at `index` (0 = serialNumber) and the TBS end.
+fn tbs_field(cert_der: &[u8], index: usize) -> Option<(usize, usize)> {
+    let (_, cert_start, _) = read_tlv(cert_der, 0)?;
+    let (_, tbs_start, tbs_end) = read_tlv(cert_der, cert_start)?;
+
+    let mut pos = tbs_start;
+    let (tag, _, end) = read_tlv(cert_der, pos)?;
+    if tag == 0xa0 {
+        pos = end; // explicit [0] version
+    }
+    for _ in 0..index {
+        pos = read_tlv(cert_der, pos)?.2;
+    }
+    Some((pos, tbs_end))
+}
+
+pub fn subject_public_key_info(cert_der: &[u8]) -> Option<&[u8]> {
+    // serialNumber, signature, issuer, validity, subject
+    let (pos, tbs_end) = tbs_field(cert_der, 5)?;
+    let (tag, _, end) = read_tlv(cert_der, pos)?;
+    (tag == 0x30 && end <= tbs_end).then(|| &cert_der[pos..end])
+}
+
+// Days since 1970-01-01 for a proleptic Gregorian date.
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// UTCTime (YYMMDDHHMMSSZ) or GeneralizedTime (YYYYMMDDHHMMSSZ) as Unix seconds.
+fn parse_time(tag: u8, value: &[u8]) -> Option<i64> {
+    let text = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        0x17 => {
+            let year: i64 = text.get(..2)?.parse().ok()?;
+            (
+                if year < 50 { 2000 + year } else { 1900 + year },
+                &text[2..],
+            )
+        }
+        0x18 => (text.get(..4)?.parse().ok()?, &text[4..]),
+        _ => return None,
+    };
+    let field = |i: usize| -> Option<i64> { rest.get(i * 2..i * 2 + 2)?.parse().ok() };
+    let days = days_from_civil(year, field(0)?, field(1)?);
+    Some(days * 86_400 + field(2)? * 3600 + field(3)? * 60 + field(4)?)
+}
+
+pub fn not_after(cert_der: &[u8]) -> Option<i64> {
+    // serialNumber, signature, issuer, then validity ::= SEQUENCE { notBefore, notAfter }
+    let (pos, _) = tbs_field(cert_der, 3)?;
+    let (tag, start, _) = read_tlv(cert_der, pos)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (_, _, not_before_end) = read_tlv(cert_der, start)?;
+    let (tag, value_start, value_end) = read_tlv(cert_der, not_before_end)?;
+    parse_time(tag, &cert_der[value_start..value_end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{not_after, subject_public_key_info};
+    use crate::digest::sha256;
+    use crate::encoding::{base64_decode, base64_encode};
+
+    // Self-signed P-256 certificate for CN=meowping.test.
+    const CERT: &str = concat!(
+        "MIIBhjCCASugAwIBAgIUfxm31IzhKn4+vvDizBy287DGYUIwCgYIKoZIzj0EAwIwGDEWMBQGA1UE",
+        "AwwNbWVvd3BpbmcudGVzdDAeFw0yNjEwMTgxOTU1NDBaFw0zNjEwMTUxOTU1NDBaMBgxFjAUBgNV",
+        "BAMMDW1lb3dwaW5nLnRlc3QwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAAS2CPfpNyIHiikUPKuA",
+        "SJG9OqQ9i3QUCpiMMlpHqO5Q7cGu/2K68DeOJIeJl8W+iBfK/MHo5A9aeqWGxCvF6Qngo1MwUTAd",
+        "BgNVHQ4EFgQUuovzTSFYae5/CnVHuuWiRcD61qEwHwYDVR0jBBgwFoAUuovzTSFYae5/CnVHuuWi",
+        "RcD61qEwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNJADBGAiEAvkZYQaQFTxsOKHI9zMOR",
+        "J4Dda51Il8o8NfgUQBoE6RACIQCMxdLU60342HV2WCZpHlrovYWMHHo0asS03U8e/dYlSg==",
+    );
+
+    #[test]
+    fn spki_pin_matches_openssl() {
+        let der = base64_decode(CERT).unwrap();
+        assert_eq!(
+            base64_encode(&sha256(&der)),
+            "dYFc0xOcXyPIrBgvrb/SofyNETb4W3mqD0wSpiGWOQw="
+        );
+        let spki = subject_public_key_info(&der).unwrap();
+        assert_eq!(
+            base64_encode(&sha256(spki)),
+            "JO/p/aokVDXJoiuQWvW0IHn9n8IKnr2Xj1G7M9TrKh8="
+        );
+    }
+
+    #[test]
+    fn not_after_is_unix_time() {
+        let der = base64_decode(CERT).unwrap();
+        // Oct 15 19:55:40 2036 GMT
+        assert_eq!(not_after(&der), Some(2_107_713_340));
+    }
+
+    #[test]
+    fn truncated_certificate_is_rejected() {
+        let der = base64_decode(CERT).unwrap();
+        assert_eq!(subject_public_key_info(&der[..100]), None);
+        assert_eq!(not_after(&der[..20]), None);
+        assert_eq!(subject_public_key_info(&[]), None);
+    }
+}